@@ -0,0 +1,74 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oem_cp::code_table::{CP437_TABLE, CP874_TABLE, ENCODING_TABLE_CP437, ENCODING_TABLE_CP874};
+use oem_cp::code_table_type::TableType;
+use oem_cp::{encode_string_checked, encode_string_lossy, OEMCPHashMap};
+
+const LEN: usize = 4096;
+
+/// Inputs (name, bytes) for a code page
+fn inputs(table: &TableType) -> Vec<(&'static str, Vec<u8>)> {
+    let ascii = (0..LEN).map(|i| b' ' + (i % 95) as u8).collect::<Vec<u8>>();
+    // Only defined bytes so that checked decoding doesn't bail out
+    let high = (0x80..=0xFF)
+        .filter(|byte| table.decode_char_checked(*byte).is_some())
+        .cycle()
+        .take(LEN)
+        .collect::<Vec<u8>>();
+    let mixed = ascii
+        .iter()
+        .zip(&high)
+        .enumerate()
+        .map(|(i, (ascii, high))| if i % 4 == 0 { *high } else { *ascii })
+        .collect::<Vec<u8>>();
+    vec![("ascii", ascii), ("mixed", mixed), ("high", high)]
+}
+
+fn bench_page(
+    c: &mut Criterion,
+    code_page: u16,
+    decoding_table: &TableType,
+    encoding_table: &OEMCPHashMap<char, u8>,
+) {
+    let mut group = c.benchmark_group(format!("cp{code_page}"));
+    group.throughput(Throughput::Bytes(LEN as u64));
+    for (name, bytes) in inputs(decoding_table) {
+        let text = decoding_table.decode_string_lossy(&bytes);
+        group.bench_with_input(
+            BenchmarkId::new("decode_lossy", name),
+            &bytes,
+            |b, bytes| b.iter(|| decoding_table.decode_string_lossy(black_box(bytes))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("decode_checked", name),
+            &bytes,
+            |b, bytes| b.iter(|| decoding_table.decode_string_checked(black_box(bytes))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("decode_assume_valid", name),
+            &bytes,
+            // SAFETY: `inputs` contains only defined bytes
+            |b, bytes| {
+                b.iter(|| unsafe { decoding_table.decode_string_assume_valid(black_box(bytes)) })
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("encode_lossy", name), &text, |b, text| {
+            b.iter(|| encode_string_lossy(black_box(text), encoding_table))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("encode_checked", name),
+            &text,
+            |b, text| b.iter(|| encode_string_checked(black_box(text), encoding_table)),
+        );
+    }
+    group.finish();
+}
+
+fn codec(c: &mut Criterion) {
+    bench_page(c, 437, &CP437_TABLE, &ENCODING_TABLE_CP437);
+    bench_page(c, 874, &CP874_TABLE, &ENCODING_TABLE_CP874);
+}
+
+criterion_group!(benches, codec);
+criterion_main!(benches);
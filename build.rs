@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
@@ -22,18 +22,28 @@ struct CodeTables {
     tables: Vec<(u16, Table)>,
 }
 
+/// Dialects selectable with `--cfg oem_cp_dialect="..."` (the first one is the default)
+///
+/// * `win` - mappings compatible with Windows API (`MultiByteToWideChar`); some undefined codepoints are mapped to C1 controls (`assets/code_tables_patch_win.json`)
+/// * `dos` - historical mappings in `assets/code_tables.json` as is
+const DIALECTS: [&str; 2] = ["win", "dos"];
+
 fn main() -> io::Result<()> {
-    generate_tables()?;
+    let dialect = select_dialect();
+    let code_tables = parse_code_tables(&dialect)?;
+
+    generate_tables(&code_tables)?;
+    generate_cp_impl(&code_tables)?;
+    generate_codepage(&code_tables, &parse_aliases()?)?;
 
     Ok(())
 }
 
 /// Generates `$OUT_DIR/code_table.rs` from `./assets/code_tables.json`
-fn generate_tables() -> io::Result<()> {
-    let code_tables = parse_code_tables()?;
-    let mut output = open_output()?;
+fn generate_tables(code_tables: &CodeTables) -> io::Result<()> {
+    let mut output = open_output("code_table.rs")?;
 
-    write_header(&mut output, code_tables.created)?;
+    write_header(&mut output, &code_tables.created)?;
 
     for (code_page, table) in &code_tables.tables {
         write_decoding(&mut output, *code_page, table)?;
@@ -45,34 +55,273 @@ fn generate_tables() -> io::Result<()> {
 
     write_decoding_table_cp_map(&mut output, &code_tables.tables)?;
     write_encoding_table_cp_map(&mut output, &code_tables.tables)?;
+    write_decoding_array_fns(&mut output, &code_tables.tables)?;
+    if env::var_os("CARGO_FEATURE_FULL_TABLES").is_some() {
+        write_full_tables(&mut output, &code_tables.tables)?;
+    }
+    write_case_tables(&mut output, &code_tables.tables)?;
 
     write_footer(&mut output)?;
 
     Ok(())
 }
 
-fn open_output() -> io::Result<BufWriter<File>> {
+/// Generates `$OUT_DIR/cp_impl.rs`, which implements `Cp<ID>` for each code page
+fn generate_cp_impl(code_tables: &CodeTables) -> io::Result<()> {
+    let mut output = open_output("cp_impl.rs")?;
+
+    for (code_page, table) in &code_tables.tables {
+        let ty = match table {
+            Table::Complete(_) => "Complete",
+            Table::Incomplete(_) => "Incomplete",
+        };
+        writeln!(
+            &mut output,
+            "cp_impl!({ty}, {code_page}, DECODING_TABLE_CP{code_page}, ENCODING_TABLE_CP{code_page}, CP{code_page}_TABLE);
+/// Single byte character in CP{code_page}
+pub type Cp{code_page} = Cp<{code_page}>;
+/// Bytes encoded in CP{code_page}
+#[cfg(feature = \"alloc\")]
+pub type Cp{code_page}Bytes<'a> = crate::CpBytes<'a, {code_page}>;
+/// Unicode string to be encoded in CP{code_page}
+#[cfg(feature = \"alloc\")]
+pub type Cp{code_page}Text<'a> = crate::CpText<'a, {code_page}>;
+"
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Generates `$OUT_DIR/codepage.rs`, which defines `Codepage` and its ID-dependent methods
+fn generate_codepage(
+    code_tables: &CodeTables,
+    aliases: &HashMap<u16, Vec<String>>,
+) -> io::Result<()> {
+    let mut output = open_output("codepage.rs")?;
+
+    writeln!(
+        &mut output,
+        "/// Code pages supported by this crate
+///
+/// Variants are listed in ascending order of code page ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+#[repr(u16)]
+pub enum Codepage {{"
+    )?;
+    for (code_page, _table) in &code_tables.tables {
+        writeln!(
+            &mut output,
+            "    /// CP{code_page}
+    Cp{code_page} = {code_page},"
+        )?;
+    }
+    writeln!(&mut output, "}}")?;
+    writeln!(&mut output)?;
+
+    writeln!(&mut output, "impl Codepage {{")?;
+    writeln!(
+        &mut output,
+        "    const ALL: [Codepage; {len}] = [",
+        len = code_tables.tables.len()
+    )?;
+    for (code_page, _table) in &code_tables.tables {
+        writeln!(&mut output, "        Codepage::Cp{code_page},")?;
+    }
+    writeln!(&mut output, "    ];")?;
+    writeln!(
+        &mut output,
+        "
+    /// Returns the code page whose ID is `id`, or `None` if it isn't supported
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert_eq!(Codepage::from_id(437), Some(Codepage::Cp437));
+    /// // CP932 (Shift-JIS; Japanese MBCS) is unsupported
+    /// assert_eq!(Codepage::from_id(932), None);
+    /// ```
+    pub const fn from_id(id: u16) -> Option<Self> {{
+        match id {{"
+    )?;
+    for (code_page, _table) in &code_tables.tables {
+        writeln!(
+            &mut output,
+            "            {code_page} => Some(Codepage::Cp{code_page}),"
+        )?;
+    }
+    writeln!(
+        &mut output,
+        "            _ => None,
+        }}
+    }}
+
+    /// Returns the name of the code page (e.g. `\"CP437\"`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert_eq!(Codepage::Cp437.name(), \"CP437\");
+    /// ```
+    pub const fn name(self) -> &'static str {{
+        match self {{"
+    )?;
+    for (code_page, _table) in &code_tables.tables {
+        writeln!(
+            &mut output,
+            "            Codepage::Cp{code_page} => \"CP{code_page}\","
+        )?;
+    }
+    writeln!(
+        &mut output,
+        "        }}
+    }}
+
+    /// Returns the known aliases of the code page (from `assets/aliases.json`)
+    ///
+    /// [`Codepage::from_label`] (and so `FromStr`) accepts all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert!(Codepage::Cp437.alias_names().contains(&\"IBM437\"));
+    /// assert!(Codepage::Cp437.alias_names().contains(&\"OEM-US\"));
+    /// ```
+    pub const fn alias_names(self) -> &'static [&'static str] {{
+        match self {{"
+    )?;
+    for (code_page, _table) in &code_tables.tables {
+        let aliases = aliases
+            .get(code_page)
+            .unwrap_or_else(|| panic!("no aliases for CP{code_page} in assets/aliases.json"));
+        writeln!(
+            &mut output,
+            "            Codepage::Cp{code_page} => &{aliases:?},"
+        )?;
+    }
+    writeln!(
+        &mut output,
+        "        }}
+    }}
+
+    /// Returns the decoding table of the code page (`CP###_TABLE`)
+    ///
+    /// It's resolved by `match` without looking up `DECODING_TABLE_CP_MAP`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert_eq!(Codepage::Cp437.decoding().decode_char_checked(0xFB), Some('√'));
+    /// ```
+    pub fn decoding(&self) -> &'static TableType {{
+        match self {{"
+    )?;
+    for (code_page, _table) in &code_tables.tables {
+        writeln!(
+            &mut output,
+            "            Codepage::Cp{code_page} => &CP{code_page}_TABLE,"
+        )?;
+    }
+    writeln!(
+        &mut output,
+        "        }}
+    }}
+
+    /// Returns the encoding table of the code page (`ENCODING_TABLE_CP###`)
+    ///
+    /// It's resolved by `match` without looking up `ENCODING_TABLE_CP_MAP`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{{encode_char_checked, Codepage}};
+    ///
+    /// assert_eq!(encode_char_checked('√', Codepage::Cp437.encoding()), Some(0xFB));
+    /// ```
+    pub fn encoding(&self) -> &'static OEMCPHashMap<char, u8> {{
+        match self {{"
+    )?;
+    for (code_page, _table) in &code_tables.tables {
+        writeln!(
+            &mut output,
+            "            Codepage::Cp{code_page} => &ENCODING_TABLE_CP{code_page},"
+        )?;
+    }
+    writeln!(
+        &mut output,
+        "        }}
+    }}
+}}"
+    )?;
+
+    Ok(())
+}
+
+/// Opens `assets/aliases.json` and returns the aliases of each code page
+fn parse_aliases() -> io::Result<HashMap<u16, Vec<String>>> {
+    let mut path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.push("assets");
+    path.push("aliases.json");
+    let file = BufReader::new(File::open(path)?);
+    let raw: HashMap<String, Vec<String>> = serde_json::from_reader(file).unwrap();
+    Ok(raw
+        .into_iter()
+        .map(|(code_page, aliases)| (code_page.parse().unwrap(), aliases))
+        .collect())
+}
+
+/// Reads `--cfg oem_cp_dialect="..."` and sets `oem_cp_dialect` to the default if it's not specified
+fn select_dialect() -> String {
+    println!(
+        "cargo:rustc-check-cfg=cfg(oem_cp_dialect, values({}))",
+        DIALECTS.map(|dialect| format!("\"{dialect}\"")).join(", ")
+    );
+    match env::var("CARGO_CFG_OEM_CP_DIALECT") {
+        Ok(dialect) => {
+            assert!(
+                DIALECTS.contains(&dialect.as_str()),
+                "unknown oem_cp_dialect {dialect:?} (expected one of {DIALECTS:?})"
+            );
+            dialect
+        }
+        Err(_) => {
+            let dialect = DIALECTS[0];
+            println!("cargo:rustc-cfg=oem_cp_dialect=\"{dialect}\"");
+            dialect.to_string()
+        }
+    }
+}
+
+fn open_output(file_name: &str) -> io::Result<BufWriter<File>> {
     let path = {
         let mut path = PathBuf::from(env::var("OUT_DIR").unwrap());
-        path.push("code_table.rs");
+        path.push(file_name);
         path
     };
     let output = BufWriter::new(File::create(path)?);
     Ok(output)
 }
 
-/// Opens `assets/code_tables.json`, and organizes and returns its contents
-fn parse_code_tables() -> io::Result<CodeTables> {
+/// Opens `assets/code_tables.json`, applies `assets/code_tables_patch_{dialect}.json` (if any), and organizes and returns its contents
+fn parse_code_tables(dialect: &str) -> io::Result<CodeTables> {
     let (path, patch_path) = {
         let mut path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
         path.push("assets");
         let mut path2 = path.clone();
         path.push("code_tables.json");
-        path2.push("code_tables_patch_win.json");
+        path2.push(format!("code_tables_patch_{dialect}.json"));
         (path, path2)
     };
     let file = BufReader::new(File::open(path)?);
-    let patch_file = BufReader::new(File::open(patch_path)?);
 
     /// Raw data structure defined in `assets/code_tables.json`
     #[derive(Deserialize)]
@@ -82,8 +331,11 @@ fn parse_code_tables() -> io::Result<CodeTables> {
     }
 
     let JsonCodeTables { created, tables } = serde_json::from_reader(file).unwrap();
-    let raw_patch: HashMap<String, HashMap<String, u32>> =
-        serde_json::from_reader(patch_file).unwrap();
+    let raw_patch: HashMap<String, HashMap<String, u32>> = if patch_path.exists() {
+        serde_json::from_reader(BufReader::new(File::open(patch_path)?)).unwrap()
+    } else {
+        HashMap::new()
+    };
 
     let patch: HashMap<String, HashMap<u8, u32>> = raw_patch
         .into_iter()
@@ -136,7 +388,7 @@ fn parse_code_tables() -> io::Result<CodeTables> {
     Ok(CodeTables { created, tables })
 }
 
-fn write_header(mut dst: impl Write, created: String) -> io::Result<()> {
+fn write_header(mut dst: impl Write, created: &str) -> io::Result<()> {
     writeln!(
         &mut dst,
         "/// Code table
@@ -146,6 +398,18 @@ pub mod code_table {{
 use super::code_table_type::TableType;
 use super::OEMCPHashMap;
 use TableType::*;
+
+/// Checks if an incomplete table really has undefined codepoints (for compile-time assertions)
+const fn has_undefined(table: &[Option<char>; 128]) -> bool {{
+    let mut i = 0;
+    while i < table.len() {{
+        if table[i].is_none() {{
+            return true;
+        }}
+        i += 1;
+    }}
+    false
+}}
 "
     )
 }
@@ -154,6 +418,7 @@ fn write_decoding(mut dst: impl Write, code_page: u16, table: &Table) -> io::Res
     writeln!(&mut dst, "/// Decoding table (CP{code_page} to Unicode)")?;
     match table {
         Table::Complete(table) => {
+            // `[char; 128]` can't have undefined codepoints, so it's checked via the bitmap below
             writeln!(
                 &mut dst,
                 "pub static DECODING_TABLE_CP{code_page}: [char; 128] = {table:?};"
@@ -162,18 +427,61 @@ fn write_decoding(mut dst: impl Write, code_page: u16, table: &Table) -> io::Res
         Table::Incomplete(table) => {
             writeln!(
                 &mut dst,
-                "pub static DECODING_TABLE_CP{code_page}: [Option<char>; 128] = {table:?};"
+                "pub static DECODING_TABLE_CP{code_page}: [Option<char>; 128] = {table:?};
+const _: () = assert!(
+    has_undefined(&DECODING_TABLE_CP{code_page}),
+    \"CP{code_page} has no undefined codepoints but is generated as an incomplete table\"
+);"
             )?;
         }
     }
 
+    let ty = match table {
+        Table::Complete(_) => "Complete",
+        Table::Incomplete(_) => "Incomplete",
+    };
+    let bitmap = match table {
+        Table::Complete(_) => u128::MAX,
+        Table::Incomplete(table) => table
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_some())
+            .fold(0u128, |bitmap, (i, _)| bitmap | (1 << i)),
+    };
+    writeln!(
+        &mut dst,
+        "/// Bitmap of defined codepoints in CP{code_page} (bit `n` stands for `0x80 + n`)
+pub static DEFINED_BITMAP_CP{code_page}: u128 = {bitmap:#034X};
+/// Number of defined codepoints in 0x80-0xFF of CP{code_page} (128 for complete tables)
+pub const DEFINED_COUNT_CP{code_page}: u8 = {count};",
+        count = bitmap.count_ones()
+    )?;
+    if let Table::Complete(_) = table {
+        writeln!(
+            &mut dst,
+            "const _: () = assert!(
+    DEFINED_BITMAP_CP{code_page} == u128::MAX,
+    \"CP{code_page} has undefined codepoints but is generated as a complete table\"
+);"
+        )?;
+    }
+    writeln!(
+        &mut dst,
+        "/// Decoding table (CP{code_page} to Unicode) wrapped in `TableType`
+pub static CP{code_page}_TABLE: TableType = {ty}(&DECODING_TABLE_CP{code_page});"
+    )?;
+
     writeln!(&mut dst)?;
 
     Ok(())
 }
 
+/// Writes the encoding table of a code page
+///
+/// Bytes are visited in ascending order and chars already seen are skipped, so the lowest byte wins when several bytes decode to the same char.
 fn write_encoding(mut dst: impl Write, code_page: u16, table: &Table) -> io::Result<()> {
     let mut map = phf_codegen::Map::new();
+    let mut seen = HashSet::new();
 
     match table {
         Table::Complete(table) => {
@@ -183,7 +491,9 @@ fn write_encoding(mut dst: impl Write, code_page: u16, table: &Table) -> io::Res
                 .enumerate()
                 .map(|(i, c)| (i + 0x80, c))
             {
-                map.entry(c, &i.to_string());
+                if seen.insert(c) {
+                    map.entry(c, &i.to_string());
+                }
             }
         }
         Table::Incomplete(table) => {
@@ -193,7 +503,9 @@ fn write_encoding(mut dst: impl Write, code_page: u16, table: &Table) -> io::Res
                 .enumerate()
                 .filter_map(|(i, c)| c.map(|c| (i + 0x80, c)))
             {
-                map.entry(c, &i.to_string());
+                if seen.insert(c) {
+                    map.entry(c, &i.to_string());
+                }
             }
         }
     }
@@ -201,6 +513,8 @@ fn write_encoding(mut dst: impl Write, code_page: u16, table: &Table) -> io::Res
     write!(
         &mut dst,
         "/// Encoding table (Unicode to CP{code_page})
+///
+/// If several bytes decode to the same char, the lowest one is chosen.
 pub static ENCODING_TABLE_CP{code_page}: OEMCPHashMap<char, u8> = {map};",
         map = map.build()
     )?;
@@ -288,6 +602,196 @@ pub static ENCODING_TABLE_CP_MAP: OEMCPHashMap<u16, &'static OEMCPHashMap<char,
     Ok(())
 }
 
+/// Writes `const fn`s that look up the raw decoding arrays by code page ID
+fn write_decoding_array_fns(mut dst: impl Write, tables: &[(u16, Table)]) -> io::Result<()> {
+    writeln!(
+        &mut dst,
+        r#"/// Returns the decoding array (0x80-0xFF) of a code page without undefined codepoints in `const` contexts
+///
+/// Returns `None` if the code page is unsupported or has undefined codepoints (see [`decoding_array_incomplete_cp`]).
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::code_table::decoding_array_complete_cp;
+///
+/// const SQRT: char = match decoding_array_complete_cp(437) {{
+///     Some(array) => array[0xFB - 0x80],
+///     None => panic!(),
+/// }};
+/// assert_eq!(SQRT, '√');
+/// assert!(decoding_array_complete_cp(874).is_none());
+/// ```
+pub const fn decoding_array_complete_cp(id: u16) -> Option<&'static [char; 128]> {{
+    match id {{"#
+    )?;
+    for (code_page, table) in tables {
+        if let Table::Complete(_) = table {
+            writeln!(
+                &mut dst,
+                "        {code_page} => Some(&DECODING_TABLE_CP{code_page}),"
+            )?;
+        }
+    }
+    writeln!(
+        &mut dst,
+        r#"        _ => None,
+    }}
+}}
+
+/// Returns the decoding array (0x80-0xFF) of a code page with undefined codepoints in `const` contexts
+///
+/// Returns `None` if the code page is unsupported or has no undefined codepoints (see [`decoding_array_complete_cp`]).
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::code_table::decoding_array_incomplete_cp;
+///
+/// const UNDEFINED: Option<char> = match decoding_array_incomplete_cp(874) {{
+///     Some(array) => array[0xDB - 0x80],
+///     None => panic!(),
+/// }};
+/// assert_eq!(UNDEFINED, None);
+/// assert!(decoding_array_incomplete_cp(437).is_none());
+/// ```
+pub const fn decoding_array_incomplete_cp(id: u16) -> Option<&'static [Option<char>; 128]> {{
+    match id {{"#
+    )?;
+    for (code_page, table) in tables {
+        if let Table::Incomplete(_) = table {
+            writeln!(
+                &mut dst,
+                "        {code_page} => Some(&DECODING_TABLE_CP{code_page}),"
+            )?;
+        }
+    }
+    writeln!(
+        &mut dst,
+        r#"        _ => None,
+    }}
+}}"#
+    )?;
+
+    Ok(())
+}
+
+/// Writes 256-entry decoding tables and their map for the `full-tables` feature
+///
+/// Undefined codepoints are mapped to U+FFFD so that lookups never branch on them.
+fn write_full_tables(mut dst: impl Write, tables: &[(u16, Table)]) -> io::Result<()> {
+    let mut map = phf_codegen::Map::new();
+
+    for (code_page, table) in tables {
+        let full = (0..=255u8)
+            .map(|byte| match byte {
+                0..=127 => byte as char,
+                _ => table.get(byte).unwrap_or('\u{FFFD}'),
+            })
+            .collect::<Vec<_>>();
+        writeln!(
+            &mut dst,
+            "/// Decoding table (CP{code_page} to Unicode) for all the bytes 0x00-0xFF (U+FFFD for undefined codepoints)
+pub static FULL_DECODING_TABLE_CP{code_page}: [char; 256] = {full:?};"
+        )?;
+        map.entry(*code_page, &format!("&FULL_DECODING_TABLE_CP{code_page}"));
+    }
+
+    writeln!(
+        &mut dst,
+        r#"/// map from codepage to decoding table for all the bytes 0x00-0xFF
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::code_table::FULL_DECODING_TABLE_CP_MAP;
+/// assert_eq!(FULL_DECODING_TABLE_CP_MAP[&437][0xFB], '√');
+/// assert_eq!(FULL_DECODING_TABLE_CP_MAP[&874][0xDB], '\u{{FFFD}}');
+/// ```
+pub static FULL_DECODING_TABLE_CP_MAP: OEMCPHashMap<u16, &'static [char; 256]> = {map};"#,
+        map = map.build()
+    )?;
+
+    Ok(())
+}
+
+impl Table {
+    /// Returns the character `byte` (0x80-0xFF) is decoded to
+    fn get(&self, byte: u8) -> Option<char> {
+        match self {
+            Table::Complete(table) => Some(table[(byte & 127) as usize]),
+            Table::Incomplete(table) => table[(byte & 127) as usize],
+        }
+    }
+}
+
+/// Maps each high byte (0x80-0xFF) to the byte of its case-converted character in the same code page
+///
+/// Only simple (one-to-one) case mappings are used.
+/// Bytes whose converted characters aren't representable (or that are undefined) are mapped to themselves.
+/// If several bytes decode to the converted character, the lowest one is chosen as in the encoding tables.
+fn case_table(table: &Table, convert: impl Fn(char) -> Option<char>) -> [u8; 128] {
+    let mut encoding = HashMap::new();
+    for byte in 0..=255u8 {
+        let c = if byte < 128 {
+            byte as char
+        } else {
+            match table.get(byte) {
+                Some(c) => c,
+                None => continue,
+            }
+        };
+        encoding.entry(c).or_insert(byte);
+    }
+    let mut ret = [0u8; 128];
+    for (i, dst) in ret.iter_mut().enumerate() {
+        let byte = i as u8 + 128;
+        *dst = table
+            .get(byte)
+            .and_then(&convert)
+            .and_then(|c| encoding.get(&c).copied())
+            .unwrap_or(byte);
+    }
+    ret
+}
+
+/// Returns `c` converted by `convert` if the result is a single character
+fn simple_case(mut converted: impl Iterator<Item = char>) -> Option<char> {
+    let c = converted.next()?;
+    converted.next().is_none().then_some(c)
+}
+
+fn write_case_tables(mut dst: impl Write, tables: &[(u16, Table)]) -> io::Result<()> {
+    let mut upper_map = phf_codegen::Map::new();
+    let mut lower_map = phf_codegen::Map::new();
+
+    for (code_page, table) in tables {
+        let upper = case_table(table, |c| simple_case(c.to_uppercase()));
+        let lower = case_table(table, |c| simple_case(c.to_lowercase()));
+        writeln!(
+            &mut dst,
+            "/// Uppercase table in CP{code_page} (0x80-0xFF to bytes in CP{code_page})
+pub static UPPERCASE_TABLE_CP{code_page}: [u8; 128] = {upper:?};
+/// Lowercase table in CP{code_page} (0x80-0xFF to bytes in CP{code_page})
+pub static LOWERCASE_TABLE_CP{code_page}: [u8; 128] = {lower:?};"
+        )?;
+        upper_map.entry(*code_page, &format!("&UPPERCASE_TABLE_CP{code_page}"));
+        lower_map.entry(*code_page, &format!("&LOWERCASE_TABLE_CP{code_page}"));
+    }
+
+    writeln!(
+        &mut dst,
+        "/// map from codepage to uppercase table
+pub static UPPERCASE_TABLE_CP_MAP: OEMCPHashMap<u16, &'static [u8; 128]> = {upper_map};
+/// map from codepage to lowercase table
+pub static LOWERCASE_TABLE_CP_MAP: OEMCPHashMap<u16, &'static [u8; 128]> = {lower_map};",
+        upper_map = upper_map.build(),
+        lower_map = lower_map.build(),
+    )?;
+
+    Ok(())
+}
+
 fn write_footer(mut dst: impl Write) -> io::Result<()> {
     writeln!(&mut dst, "}}")
 }
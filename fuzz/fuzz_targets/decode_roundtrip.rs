@@ -0,0 +1,36 @@
+//! Decodes arbitrary bytes, encodes the result back, and checks that the round trip is stable
+//!
+//! The first byte of the input selects the code page.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use oem_cp::code_table::{DECODING_TABLE_CP_MAP, ENCODING_TABLE_CP_MAP};
+use oem_cp::{encode_string_checked, encode_string_lossy, Codepage};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((selector, src)) = data.split_first() else {
+        return;
+    };
+    let codepages = Codepage::all();
+    let codepage = codepages[*selector as usize % codepages.len()];
+    let decoding_table = &DECODING_TABLE_CP_MAP[&codepage.id()];
+    let encoding_table = ENCODING_TABLE_CP_MAP[&codepage.id()];
+
+    // Undefined codepoints are replaced with U+FFFD and then `?`, so it becomes stable after one round trip
+    let decoded = decoding_table.decode_string_lossy(src);
+    assert_eq!(decoded.chars().count(), src.len());
+    let encoded = encode_string_lossy(&decoded, encoding_table);
+    assert_eq!(encoded.len(), src.len());
+    let redecoded = decoding_table.decode_string_lossy(&encoded);
+    assert_eq!(encode_string_lossy(&redecoded, encoding_table), encoded);
+
+    // Valid bytes must be restored exactly
+    if let Some(decoded) = decoding_table.decode_string_checked(src) {
+        assert_eq!(decoded, decoding_table.decode_string_lossy(src));
+        assert_eq!(
+            encode_string_checked(&decoded, encoding_table).as_deref(),
+            Some(src)
+        );
+    }
+});
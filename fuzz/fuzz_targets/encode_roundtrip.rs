@@ -0,0 +1,35 @@
+//! Encodes arbitrary UTF-8, decodes the result back, and checks that the round trip is stable
+//!
+//! The first byte of the input selects the code page.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use oem_cp::code_table::{DECODING_TABLE_CP_MAP, ENCODING_TABLE_CP_MAP};
+use oem_cp::{encode_string_checked, encode_string_lossy, Codepage};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((selector, src)) = data.split_first() else {
+        return;
+    };
+    let Ok(src) = std::str::from_utf8(src) else {
+        return;
+    };
+    let codepages = Codepage::all();
+    let codepage = codepages[*selector as usize % codepages.len()];
+    let decoding_table = &DECODING_TABLE_CP_MAP[&codepage.id()];
+    let encoding_table = ENCODING_TABLE_CP_MAP[&codepage.id()];
+
+    // Unrepresentable chars are replaced with `?`, so the encoded bytes are always valid
+    let encoded = encode_string_lossy(src, encoding_table);
+    assert_eq!(encoded.len(), src.chars().count());
+    let decoded = decoding_table
+        .decode_string_checked(&encoded)
+        .expect("encoded bytes must be valid");
+    assert_eq!(encode_string_lossy(&decoded, encoding_table), encoded);
+
+    // Representable strings must be restored exactly
+    if let Some(encoded) = encode_string_checked(src, encoding_table) {
+        assert_eq!(decoding_table.decode_string_checked(&encoded).as_deref(), Some(src));
+    }
+});
@@ -0,0 +1,312 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use super::code_table::DECODING_TABLE_CP_MAP;
+use super::code_table::{LOWERCASE_TABLE_CP_MAP, UPPERCASE_TABLE_CP_MAP};
+use super::strategy::{AsciiFast, DecodeStrategy};
+
+/// Convert single SBCS (single byte character set) byte to uppercase within the code page
+///
+/// Uses simple (one-to-one) Unicode case mappings restricted to characters representable in the code page.
+/// Bytes whose uppercase isn't representable, undefined bytes, and bytes in unsupported code pages (except for ASCII) are returned unchanged.
+///
+/// # Arguments
+///
+/// * `src` - single byte encoded in SBCS
+/// * `code_page` - code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::to_upper_in_page;
+///
+/// // ř => Ř in CP852
+/// assert_eq!(to_upper_in_page(0xFD, 852), 0xFC);
+/// assert_eq!(to_upper_in_page(b'a', 852), b'A');
+/// // ÿ => Ÿ isn't representable in CP437
+/// assert_eq!(to_upper_in_page(0x98, 437), 0x98);
+/// ```
+pub fn to_upper_in_page(src: u8, code_page: u16) -> u8 {
+    match AsciiFast::decode_direct(src) {
+        Ok(_) => src.to_ascii_uppercase(),
+        Err(index) => UPPERCASE_TABLE_CP_MAP
+            .get(&code_page)
+            .map_or(src, |table| table[index]),
+    }
+}
+
+/// Convert single SBCS (single byte character set) byte to lowercase within the code page
+///
+/// Uses simple (one-to-one) Unicode case mappings restricted to characters representable in the code page.
+/// Bytes whose lowercase isn't representable, undefined bytes, and bytes in unsupported code pages (except for ASCII) are returned unchanged.
+///
+/// # Arguments
+///
+/// * `src` - single byte encoded in SBCS
+/// * `code_page` - code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::to_lower_in_page;
+///
+/// // Ř => ř in CP852
+/// assert_eq!(to_lower_in_page(0xFC, 852), 0xFD);
+/// assert_eq!(to_lower_in_page(b'A', 852), b'a');
+/// ```
+pub fn to_lower_in_page(src: u8, code_page: u16) -> u8 {
+    match AsciiFast::decode_direct(src) {
+        Ok(_) => src.to_ascii_lowercase(),
+        Err(index) => LOWERCASE_TABLE_CP_MAP
+            .get(&code_page)
+            .map_or(src, |table| table[index]),
+    }
+}
+
+/// Convert SBCS (single byte character set) bytes to uppercase within the code page in place
+///
+/// Same as applying [`to_upper_in_page`] to each byte, but the case table is looked up only once.
+///
+/// # Arguments
+///
+/// * `bytes` - bytes encoded in SBCS
+/// * `code_page` - code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::make_uppercase_in_page;
+///
+/// // "àbç" => "ÀBÇ" in CP850
+/// let mut bytes = [0x85, b'b', 0x87];
+/// make_uppercase_in_page(&mut bytes, 850);
+/// assert_eq!(bytes, [0xB7, b'B', 0x80]);
+/// ```
+pub fn make_uppercase_in_page(bytes: &mut [u8], code_page: u16) {
+    let table = UPPERCASE_TABLE_CP_MAP.get(&code_page);
+    for byte in bytes {
+        match (AsciiFast::decode_direct(*byte), table) {
+            (Ok(_), _) => byte.make_ascii_uppercase(),
+            (Err(index), Some(table)) => *byte = table[index],
+            (Err(_), None) => {}
+        }
+    }
+}
+
+/// Convert SBCS (single byte character set) bytes to lowercase within the code page in place
+///
+/// Same as applying [`to_lower_in_page`] to each byte, but the case table is looked up only once.
+///
+/// # Arguments
+///
+/// * `bytes` - bytes encoded in SBCS
+/// * `code_page` - code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::make_lowercase_in_page;
+///
+/// // "ÀBÇ" => "àbç" in CP850
+/// let mut bytes = [0xB7, b'B', 0x80];
+/// make_lowercase_in_page(&mut bytes, 850);
+/// assert_eq!(bytes, [0x85, b'b', 0x87]);
+/// ```
+pub fn make_lowercase_in_page(bytes: &mut [u8], code_page: u16) {
+    let table = LOWERCASE_TABLE_CP_MAP.get(&code_page);
+    for byte in bytes {
+        match (AsciiFast::decode_direct(*byte), table) {
+            (Ok(_), _) => byte.make_ascii_lowercase(),
+            (Err(index), Some(table)) => *byte = table[index],
+            (Err(_), None) => {}
+        }
+    }
+}
+
+/// Sort SBCS (single byte character set) byte strings (e.g. filenames) by their decoded Unicode order
+///
+/// Same as sorting with [`TableType::cmp_bytes`](crate::code_table_type::TableType::cmp_bytes), so undefined codepoints are compared as U+FFFD.
+/// Byte strings in unsupported code pages are sorted by raw bytes.
+/// The sort is stable.
+///
+/// # Arguments
+///
+/// * `names` - byte strings encoded in SBCS
+/// * `page` - code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::sort_filenames;
+///
+/// // "Ёлка", "Яблоко", "Арбуз" in CP866
+/// let mut names = vec![vec![0xF0, 0xAB, 0xAA, 0xA0], vec![0x9F, 0xA1, 0xAB, 0xAE, 0xAA, 0xAE], vec![0x80, 0xE0, 0xA1, 0xE3, 0xA7]];
+/// sort_filenames(&mut names, 866);
+/// assert_eq!(names[0][0], 0xF0); // 'Ё' (U+0401) comes before 'А' (U+0410)
+/// assert_eq!(names[1][0], 0x80);
+/// assert_eq!(names[2][0], 0x9F);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn sort_filenames(names: &mut [Vec<u8>], page: u16) {
+    match DECODING_TABLE_CP_MAP.get(&page) {
+        Some(table) => names.sort_by(|a, b| table.cmp_bytes(a, b)),
+        None => names.sort(),
+    }
+}
+
+/// Sort SBCS (single byte character set) byte strings (e.g. filenames) by their decoded Unicode order ignoring case
+///
+/// Bytes are compared after being converted to lowercase within the code page (see [`to_lower_in_page`]).
+/// Byte strings in unsupported code pages are sorted by raw bytes with only ASCII folded.
+/// The sort is stable, so names differing only in case keep their order.
+///
+/// # Arguments
+///
+/// * `names` - byte strings encoded in SBCS
+/// * `page` - code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::sort_filenames_case_insensitive;
+///
+/// // "žal", "ZEM", "ŽABA", "zima" in CP852
+/// let mut names = vec![b"\xA7al".to_vec(), b"ZEM".to_vec(), b"\xA6ABA".to_vec(), b"zima".to_vec()];
+/// sort_filenames_case_insensitive(&mut names, 852);
+/// assert_eq!(names, [b"ZEM".to_vec(), b"zima".to_vec(), b"\xA6ABA".to_vec(), b"\xA7al".to_vec()]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn sort_filenames_case_insensitive(names: &mut [Vec<u8>], page: u16) {
+    let lowercase_table = LOWERCASE_TABLE_CP_MAP.get(&page);
+    let fold = |byte: &u8| match (AsciiFast::decode_direct(*byte), lowercase_table) {
+        (Ok(_), _) => byte.to_ascii_lowercase(),
+        (Err(index), Some(table)) => table[index],
+        (Err(_), None) => *byte,
+    };
+    match DECODING_TABLE_CP_MAP.get(&page) {
+        Some(table) => names.sort_by(|a, b| {
+            let decode = |byte: &u8| table.decode_char_lossy(fold(byte));
+            a.iter().map(decode).cmp(b.iter().map(decode))
+        }),
+        None => names.sort_by(|a, b| a.iter().map(fold).cmp(b.iter().map(fold))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_table::{DECODING_TABLE_CP_MAP, ENCODING_TABLE_CP_MAP};
+    use crate::encode_char_checked;
+
+    #[test]
+    fn case_tables_test() {
+        for (cp, decoding_table) in DECODING_TABLE_CP_MAP.entries() {
+            let encoding_table = ENCODING_TABLE_CP_MAP.get(cp).unwrap();
+            for byte in 0..=255 {
+                let Some(c) = decoding_table.decode_char_checked(byte) else {
+                    assert_eq!(to_upper_in_page(byte, *cp), byte);
+                    assert_eq!(to_lower_in_page(byte, *cp), byte);
+                    continue;
+                };
+                let mut upper = c.to_uppercase();
+                let expected = match (upper.next(), upper.next()) {
+                    (Some(upper), None) => encode_char_checked(upper, encoding_table),
+                    _ => None,
+                };
+                assert_eq!(
+                    to_upper_in_page(byte, *cp),
+                    expected.unwrap_or(byte),
+                    "0x{byte:X} ({c:?}) in cp{cp}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cp852_case_test() {
+        // cspell: disable
+        let lower = [0xFD, 0xD8, 0xA5, 0x88, 0xBE, 0xAB, 0x9F]; // řěąłżźč
+        let upper = [0xFC, 0xB7, 0xA4, 0x9D, 0xBD, 0x8D, 0xAC]; // ŘĚĄŁŻŹČ
+                                                                // cspell: enable
+        for (lower, upper) in lower.into_iter().zip(upper) {
+            assert_eq!(to_upper_in_page(lower, 852), upper);
+            assert_eq!(to_lower_in_page(upper, 852), lower);
+        }
+        // Unsupported code pages
+        assert_eq!(to_upper_in_page(0xFD, 932), 0xFD);
+        assert_eq!(to_upper_in_page(b'z', 932), b'Z');
+    }
+
+    #[test]
+    fn cp861_cp865_case_test() {
+        // cspell: disable
+        let pairs = [
+            (861, 0x95, 0x8D), // þÞ
+            (861, 0x8C, 0x8B), // ðÐ
+            (861, 0xA1, 0xA5), // íÍ
+            (861, 0x91, 0x92), // æÆ
+            (865, 0x9B, 0x9D), // øØ
+            (865, 0x91, 0x92), // æÆ
+            (865, 0x86, 0x8F), // åÅ
+        ];
+        // cspell: enable
+        for (cp, lower, upper) in pairs {
+            assert_eq!(to_upper_in_page(lower, cp), upper, "0x{lower:X} in cp{cp}");
+            assert_eq!(to_lower_in_page(upper, cp), lower, "0x{upper:X} in cp{cp}");
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sort_filenames_test() {
+        // "Ёж", "ёлка", "Арбуз", "абак" in CP866
+        let original = vec![
+            vec![0xF0, 0xA6],
+            vec![0xF1, 0xAB, 0xAA, 0xA0],
+            vec![0x80, 0xE0, 0xA1, 0xE3, 0xA7],
+            vec![0xA0, 0xA1, 0xA0, 0xAA],
+        ];
+        let table = &DECODING_TABLE_CP_MAP[&866];
+        let mut names = original.clone();
+        sort_filenames(&mut names, 866);
+        let decoded = names
+            .iter()
+            .map(|name| table.decode_string_lossy(name))
+            .collect::<Vec<_>>();
+        let mut expected = decoded.clone();
+        expected.sort();
+        assert_eq!(decoded, expected);
+
+        let mut names = original.clone();
+        sort_filenames_case_insensitive(&mut names, 866);
+        let expected = [3, 2, 0, 1].map(|i| original[i].clone());
+        assert_eq!(names, expected);
+
+        // Unsupported code pages
+        let mut names = vec![b"b".to_vec(), b"B".to_vec(), b"a".to_vec()];
+        sort_filenames(&mut names, 932);
+        assert_eq!(names, [b"B".to_vec(), b"a".to_vec(), b"b".to_vec()]);
+        sort_filenames_case_insensitive(&mut names, 932);
+        assert_eq!(names, [b"a".to_vec(), b"B".to_vec(), b"b".to_vec()]);
+    }
+
+    #[test]
+    fn make_case_in_page_test() {
+        for code_page in [437, 850, 852, 866, 874, 932] {
+            let original = (0..=255).collect::<Vec<u8>>();
+            let mut lower = original.clone();
+            let mut upper = original.clone();
+            make_lowercase_in_page(&mut lower, code_page);
+            make_uppercase_in_page(&mut upper, code_page);
+            for byte in original {
+                assert_eq!(lower[byte as usize], to_lower_in_page(byte, code_page));
+                assert_eq!(upper[byte as usize], to_upper_in_page(byte, code_page));
+            }
+        }
+        // "ÀBÇ" => "àbç" in CP850
+        let mut bytes = [0xB7, b'B', 0x80];
+        make_lowercase_in_page(&mut bytes, 850);
+        assert_eq!(bytes, [0x85, b'b', 0x87]);
+    }
+}
@@ -0,0 +1,1126 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ops::RangeInclusive;
+
+use super::code_table::{DECODING_TABLE_CP_MAP, ENCODING_TABLE_CP_MAP};
+use super::code_table_type::TableType;
+use super::strategy::{AsciiFast, DecodeStrategy, EncodeStrategy};
+use super::{OEMCPHashMap, TryFromCharError, TryFromU8Error};
+
+use TableType::*;
+
+impl TableType {
+    /// Wrapper function for decoding single byte encoded in SBCSs
+    ///
+    /// If the byte is undefined in the code page, returns `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte` - single byte encoded in SBCS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::DECODING_TABLE_CP874;
+    /// use oem_cp::code_table_type::TableType::Incomplete;
+    ///
+    /// assert_eq!(Incomplete(&DECODING_TABLE_CP874).decode_char_checked(0xE9), Some('\u{E49}'));
+    /// assert_eq!(Incomplete(&DECODING_TABLE_CP874).decode_char_checked(0xDB), None);
+    /// ```
+    pub fn decode_char_checked(&self, byte: u8) -> Option<char> {
+        match self {
+            Complete(table_ref) => Some(decode_char_complete_table(byte, table_ref)),
+            Incomplete(table_ref) => decode_char_incomplete_table_checked(byte, table_ref),
+        }
+    }
+
+    /// Wrapper function for decoding single byte encoded in SBCSs
+    ///
+    /// Undefined codepoints are replaced with U+FFFD.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte` - single byte encoded in SBCS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::DECODING_TABLE_CP874;
+    /// use oem_cp::code_table_type::TableType::Incomplete;
+    ///
+    /// assert_eq!(Incomplete(&DECODING_TABLE_CP874).decode_char_lossy(0x85), '…');
+    /// assert_eq!(Incomplete(&DECODING_TABLE_CP874).decode_char_lossy(0xDB), '\u{FFFD}');
+    /// ```
+    pub fn decode_char_lossy(&self, byte: u8) -> char {
+        match self {
+            Complete(table_ref) => decode_char_complete_table(byte, table_ref),
+            Incomplete(table_ref) => decode_char_incomplete_table_lossy(byte, table_ref),
+        }
+    }
+
+    /// Wrapper function for decoding single byte encoded in SBCSs to a UTF-16 code unit
+    ///
+    /// All supported code pages are mapped into the BMP, so the result is always a single code unit (never a surrogate).
+    /// This function returns `None` if `byte` is an undefined codepoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte` - single byte encoded in SBCS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::{DECODING_TABLE_CP437, DECODING_TABLE_CP874};
+    /// use oem_cp::code_table_type::TableType::{Complete, Incomplete};
+    ///
+    /// assert_eq!(Complete(&DECODING_TABLE_CP437).decode_char_u16(0xFB), Some(0x221A));
+    /// assert_eq!(Incomplete(&DECODING_TABLE_CP874).decode_char_u16(0xDB), None);
+    /// ```
+    pub fn decode_char_u16(&self, byte: u8) -> Option<u16> {
+        self.decode_char_checked(byte).map(|c| c as u16)
+    }
+
+    /// Checks if a single byte encoded in SBCSs is decoded to a printable character
+    ///
+    /// Returns `false` for undefined codepoints and characters in the C0 (U+0000-U+001F), DEL (U+007F), and C1 (U+0080-U+009F) control ranges, and `true` otherwise.
+    /// This is a rough classification for sanitizing output; it doesn't look up Unicode general categories.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte` - single byte encoded in SBCS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::{DECODING_TABLE_CP437, DECODING_TABLE_CP874};
+    /// use oem_cp::code_table_type::TableType::{Complete, Incomplete};
+    ///
+    /// // bell
+    /// assert!(!Complete(&DECODING_TABLE_CP437).byte_is_printable(0x07));
+    /// assert!(Complete(&DECODING_TABLE_CP437).byte_is_printable(0xFB));
+    /// assert!(!Incomplete(&DECODING_TABLE_CP874).byte_is_printable(0xDB));
+    /// ```
+    pub fn byte_is_printable(&self, byte: u8) -> bool {
+        self.decode_char_checked(byte)
+            .is_some_and(|c| !c.is_control())
+    }
+
+    /// Returns the whole mapping of the code page (0x00-0xFF)
+    ///
+    /// 0x00-0x7F are mapped to the ASCII characters, and 0x80-0xFF are looked up in the table.
+    /// Undefined codepoints are `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::{DECODING_TABLE_CP437, DECODING_TABLE_CP874};
+    /// use oem_cp::code_table_type::TableType::{Complete, Incomplete};
+    ///
+    /// let mapping = Complete(&DECODING_TABLE_CP437).full_mapping();
+    /// assert_eq!(mapping[0x41], Some('A'));
+    /// assert_eq!(mapping[0xFB], Some('√'));
+    /// assert_eq!(Incomplete(&DECODING_TABLE_CP874).full_mapping()[0xDB], None);
+    /// ```
+    pub fn full_mapping(&self) -> [Option<char>; 256] {
+        core::array::from_fn(|byte| self.decode_char_checked(byte as u8))
+    }
+
+    /// Returns an iterator over undefined bytes in ascending order
+    ///
+    /// It's always empty for complete tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::{CP437_TABLE, CP874_TABLE};
+    ///
+    /// assert_eq!(CP437_TABLE.undefined_bytes().count(), 0);
+    /// assert!(CP874_TABLE.undefined_bytes().eq([0xDB, 0xDC, 0xDD, 0xDE, 0xFC, 0xFD, 0xFE, 0xFF]));
+    /// ```
+    pub fn undefined_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.iter()
+            .filter_map(|(byte, c)| c.is_none().then_some(byte))
+    }
+
+    /// Returns an iterator over the mapping of 0x80-0xFF as `(byte, Option<char>)`
+    ///
+    /// Same as `(&table).into_iter()`. Use [`TableIter::full`] to include the ASCII range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::CP874_TABLE;
+    ///
+    /// let mut iter = CP874_TABLE.iter();
+    /// assert_eq!(iter.len(), 128);
+    /// assert_eq!(iter.next(), Some((0x80, Some('€'))));
+    /// assert_eq!(CP874_TABLE.iter().last(), Some((0xFF, None)));
+    /// for (byte, c) in &CP874_TABLE {
+    ///     assert!(byte >= 0x80);
+    /// }
+    /// ```
+    pub fn iter(&self) -> TableIter<'_> {
+        TableIter {
+            table: self,
+            bytes: 0x80..=0xFF,
+        }
+    }
+
+    /// Compares bytes encoded in SBCSs lexicographically by the decoded codepoints
+    ///
+    /// The result is the same as comparing the decoded strings, but nothing is allocated.
+    /// It differs from comparing raw bytes because the order of codepoints isn't kept in 0x80-0xFF.
+    /// Undefined codepoints are compared as U+FFFD.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - bytes encoded in SBCS
+    /// * `b` - bytes encoded in SBCS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use oem_cp::code_table::DECODING_TABLE_CP866;
+    /// use oem_cp::code_table_type::TableType::Complete;
+    ///
+    /// let table = Complete(&DECODING_TABLE_CP866);
+    /// // "Ё" (U+0401; 0xF0) < "А" (U+0410; 0x80)
+    /// assert_eq!(table.cmp_bytes(&[0xF0], &[0x80]), Ordering::Less);
+    /// assert_eq!(table.cmp_bytes(&[0x80], &[0x80, 0x80]), Ordering::Less);
+    ///
+    /// let mut words: Vec<&[u8]> = vec![&[0x80, 0x41], &[0xF0], &[0x41]];
+    /// words.sort_by(|a, b| table.cmp_bytes(a, b));
+    /// assert_eq!(words, [&[0x41][..], &[0xF0], &[0x80, 0x41]]);
+    /// ```
+    pub fn cmp_bytes(&self, a: &[u8], b: &[u8]) -> Ordering {
+        let decode = |byte: &u8| self.decode_char_lossy(*byte);
+        a.iter().map(decode).cmp(b.iter().map(decode))
+    }
+
+    /// Decode only the byte at `index` of bytes encoded in SBCS
+    ///
+    /// Each byte is a char in SBCS, so this is a single table lookup without decoding the whole buffer.
+    /// Undefined codepoints are replaced with U+FFFD.
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - bytes encoded in SBCS
+    /// * `index` - index of the byte (and the char)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::CP874_TABLE;
+    ///
+    /// assert_eq!(CP874_TABLE.char_at(&[0x30, 0xE9, 0xDB], 1), Some('\u{E49}'));
+    /// assert_eq!(CP874_TABLE.char_at(&[0x30, 0xE9, 0xDB], 2), Some('\u{FFFD}'));
+    /// assert_eq!(CP874_TABLE.char_at(&[0x30, 0xE9, 0xDB], 3), None);
+    /// ```
+    pub fn char_at(&self, src: &[u8], index: usize) -> Option<char> {
+        src.get(index).map(|byte| self.decode_char_lossy(*byte))
+    }
+
+    /// Wrapper function for decoding bytes encoded in SBCSs into a callback without allocation
+    ///
+    /// `sink` is called for each decoded char in order.
+    /// Undefined codepoints are replaced with U+FFFD.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - bytes encoded in SBCS
+    /// * `sink` - callback receiving decoded chars
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::CP874_TABLE;
+    ///
+    /// let mut decoded = String::new();
+    /// CP874_TABLE.decode_to_sink(&[0xA1, 0xD8, 0xDB], |c| decoded.push(c));
+    /// assert_eq!(decoded, "กุ\u{FFFD}");
+    /// ```
+    pub fn decode_to_sink<F: FnMut(char)>(&self, src: &[u8], mut sink: F) {
+        for &byte in src {
+            sink(self.decode_char_lossy(byte));
+        }
+    }
+
+    /// Wrapper function for decoding bytes encoded in SBCSs into a [`fmt::Write`] without allocation
+    ///
+    /// Undefined codepoints are replaced with U+FFFD.
+    /// Stops at and returns the first error from `w`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - bytes encoded in SBCS
+    /// * `w` - destination of decoded chars (e.g. `String` or `fmt::Formatter`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    /// use oem_cp::code_table::CP437_TABLE;
+    ///
+    /// let mut out = String::from("answer: ");
+    /// CP437_TABLE.decode_to_fmt(&[0xFB, 0xAB], &mut out)?;
+    /// write!(out, "!")?;
+    /// assert_eq!(out, "answer: √½!");
+    /// # Ok::<(), std::fmt::Error>(())
+    /// ```
+    pub fn decode_to_fmt<W: fmt::Write>(&self, src: &[u8], w: &mut W) -> fmt::Result {
+        src.iter()
+            .try_for_each(|&byte| w.write_char(self.decode_char_lossy(byte)))
+    }
+
+    /// Wrapper function for decoding bytes encoded in SBCSs into a UTF-8 buffer without allocation
+    ///
+    /// Undefined codepoints are replaced with U+FFFD.
+    /// Each byte takes up to 3 bytes in UTF-8 (all supported code pages are mapped into the BMP), so `dst` of `3 * src.len()` bytes is always enough.
+    /// This function returns the number of bytes written to `dst`, or `None` if `dst` is too short (then `dst` is partially overwritten).
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - bytes encoded in SBCS
+    /// * `dst` - buffer to write the UTF-8 string into
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::DECODING_TABLE_CP437;
+    /// use oem_cp::code_table_type::TableType::Complete;
+    ///
+    /// let mut buf = [0; 16];
+    /// let len = Complete(&DECODING_TABLE_CP437).decode_to_slice(&[0xFB, 0xAC, 0x3D, 0xAB], &mut buf).unwrap();
+    /// assert_eq!(core::str::from_utf8(&buf[..len]), Ok("√¼=½"));
+    /// assert_eq!(Complete(&DECODING_TABLE_CP437).decode_to_slice(&[0xFB, 0xAC], &mut buf[..4]), None);
+    /// ```
+    pub fn decode_to_slice(&self, src: &[u8], dst: &mut [u8]) -> Option<usize> {
+        let mut len = 0;
+        for &byte in src {
+            let c = self.decode_char_lossy(byte);
+            let end = len + c.len_utf8();
+            c.encode_utf8(dst.get_mut(len..end)?);
+            len = end;
+        }
+        Some(len)
+    }
+
+    /// Wrapper function for decoding a fixed-width field encoded in SBCSs into a fixed-size UTF-8 buffer
+    ///
+    /// Same as [`decode_to_slice`](Self::decode_to_slice), but it never fails because `M` must be at least `3 * N` (checked at compile time).
+    /// This function returns the number of bytes written to `dst`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - fixed-width bytes encoded in SBCS
+    /// * `dst` - buffer to write the UTF-8 string into
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::DECODING_TABLE_CP874;
+    /// use oem_cp::code_table_type::TableType::Incomplete;
+    ///
+    /// // means shrimp in Thai
+    /// let mut buf = [0; 12];
+    /// let len = Incomplete(&DECODING_TABLE_CP874).decode_fixed(&[0xA1, 0xD8, 0xE9, 0xA7], &mut buf);
+    /// assert_eq!(core::str::from_utf8(&buf[..len]), Ok("กุ้ง"));
+    /// ```
+    ///
+    /// Too short buffers are rejected:
+    ///
+    /// ```compile_fail
+    /// use oem_cp::code_table::DECODING_TABLE_CP437;
+    /// use oem_cp::code_table_type::TableType::Complete;
+    ///
+    /// let mut buf = [0; 11];
+    /// Complete(&DECODING_TABLE_CP437).decode_fixed(&[0xFB; 4], &mut buf);
+    /// ```
+    pub fn decode_fixed<const N: usize, const M: usize>(
+        &self,
+        src: &[u8; N],
+        dst: &mut [u8; M],
+    ) -> usize {
+        const {
+            assert!(
+                M >= 3 * N,
+                "the buffer must be at least 3 times as long as the source"
+            )
+        };
+        // Never fails since the buffer is long enough
+        self.decode_to_slice(src, dst).unwrap_or_default()
+    }
+}
+
+/// Iterator over the mapping of a [`TableType`] as `(byte, Option<char>)`
+///
+/// Created by [`TableType::iter`] or `(&table).into_iter()`.
+/// Bytes are yielded in ascending order, with `None` for undefined bytes.
+#[derive(Debug, Clone)]
+pub struct TableIter<'a> {
+    table: &'a TableType,
+    bytes: RangeInclusive<u8>,
+}
+
+impl TableIter<'_> {
+    /// Extends the iterator to the whole byte range (0x00-0xFF) including ASCII
+    ///
+    /// Bytes already yielded are yielded again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::CP437_TABLE;
+    ///
+    /// let mut iter = CP437_TABLE.iter().full();
+    /// assert_eq!(iter.len(), 256);
+    /// assert_eq!(iter.next(), Some((0x00, Some('\0'))));
+    /// ```
+    pub fn full(self) -> Self {
+        Self {
+            bytes: 0..=0xFF,
+            ..self
+        }
+    }
+}
+
+impl Iterator for TableIter<'_> {
+    type Item = (u8, Option<char>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = self.bytes.next()?;
+        Some((byte, self.table.decode_char_checked(byte)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.bytes.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for TableIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let byte = self.bytes.next_back()?;
+        Some((byte, self.table.decode_char_checked(byte)))
+    }
+}
+
+impl ExactSizeIterator for TableIter<'_> {}
+
+impl core::iter::FusedIterator for TableIter<'_> {}
+
+impl<'a> IntoIterator for &'a TableType {
+    type Item = (u8, Option<char>);
+    type IntoIter = TableIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Entry of decoding tables
+///
+/// Implemented for `char` (e.g. `[char; 128]` in [`code_table`](crate::code_table)) and `u16` (compact tables of UTF-16 code units) so that the decoding functions serve both representations.
+pub trait DecodeEntry: Copy {
+    /// Converts the entry to the char
+    fn to_char(self) -> char;
+}
+
+impl DecodeEntry for char {
+    #[inline]
+    fn to_char(self) -> char {
+        self
+    }
+}
+
+/// Surrogates are converted to U+FFFD.
+impl DecodeEntry for u16 {
+    #[inline]
+    fn to_char(self) -> char {
+        char::from_u32(self as u32).unwrap_or('\u{FFFD}')
+    }
+}
+
+/// Decode single SBCS (single byte character set) byte (no undefined codepoints)
+///
+/// # Arguments
+///
+/// * `src` - single byte encoded in SBCS
+/// * `decoding_table` - table for decoding SBCS (with**out** undefined codepoints); `[char; 128]` or `[u16; 128]`
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::decode_char_complete_table;
+/// use oem_cp::code_table::DECODING_TABLE_CP437;
+///
+/// assert_eq!(decode_char_complete_table(0xFB, &DECODING_TABLE_CP437), '√');
+/// // Compact tables
+/// let compact = DECODING_TABLE_CP437.map(|c| c as u16);
+/// assert_eq!(decode_char_complete_table(0xFB, &compact), '√');
+/// ```
+pub fn decode_char_complete_table<E: DecodeEntry>(src: u8, decoding_table: &[E; 128]) -> char {
+    AsciiFast::decode_direct(src).unwrap_or_else(|index| decoding_table[index].to_char())
+}
+
+/// Decode single SBCS (single byte character set) byte (with undefined codepoints)
+///
+/// If some undefined codepoints are found, returns `None`.
+///
+/// # Arguments
+///
+/// * `src` - single byte encoded in SBCS
+/// * `decoding_table` - table for decoding SBCS (**with** undefined codepoints)
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::decode_char_incomplete_table_checked;
+/// use oem_cp::code_table::DECODING_TABLE_CP874;
+///
+/// assert_eq!(decode_char_incomplete_table_checked(0x85, &DECODING_TABLE_CP874), Some('…'));
+/// assert_eq!(decode_char_incomplete_table_checked(0xFC, &DECODING_TABLE_CP874), None);
+/// ```
+pub fn decode_char_incomplete_table_checked(
+    src: u8,
+    decoding_table: &[Option<char>; 128],
+) -> Option<char> {
+    AsciiFast::decode_direct(src).map_or_else(|index| decoding_table[index], Some)
+}
+
+/// Decode single SBCS (single byte character set) byte (with undefined codepoints)
+///
+/// Undefined codepoints are replaced with `U+FFFD` (replacement character).
+///
+/// # Arguments
+///
+/// * `src` - single byte encoded in SBCS
+/// * `decoding_table` - table for decoding SBCS (**with** undefined codepoints)
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::decode_char_incomplete_table_lossy;
+/// use oem_cp::code_table::DECODING_TABLE_CP874;
+///
+/// assert_eq!(decode_char_incomplete_table_lossy(0x85, &DECODING_TABLE_CP874), '…');
+/// assert_eq!(decode_char_incomplete_table_lossy(0xFC, &DECODING_TABLE_CP874), '\u{FFFD}');
+/// ```
+pub fn decode_char_incomplete_table_lossy(src: u8, decoding_table: &[Option<char>; 128]) -> char {
+    decode_char_incomplete_table_checked(src, decoding_table).unwrap_or('\u{FFFD}')
+}
+
+/// Encode Unicode char in SBCS (single byte character set)
+///
+/// If undefined codepoint is found, returns `None`.
+///
+/// # Arguments
+///
+/// * `src` - Unicode char
+/// * `encoding_table` - table for encoding in SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_char_checked;
+/// use oem_cp::code_table::{ENCODING_TABLE_CP437, ENCODING_TABLE_CP737};
+/// assert_eq!(encode_char_checked('π', &ENCODING_TABLE_CP437), Some(0xE3));
+/// // Archimedes in Greek
+/// assert_eq!(encode_char_checked('Α', &ENCODING_TABLE_CP737), Some(0x80));
+/// // Japanese characters are not defined in CP437
+/// assert_eq!(encode_char_checked('日', &ENCODING_TABLE_CP437), None);
+/// ```
+pub fn encode_char_checked(src: char, encoding_table: &OEMCPHashMap<char, u8>) -> Option<u8> {
+    AsciiFast::encode_direct(src).or_else(|| encoding_table.get(&src).copied())
+}
+
+/// Encode Unicode char in SBCS (single byte character set)
+///
+/// Undefined codepoints are replaced with `0x3F` (`?`).
+///
+/// # Arguments
+///
+/// * `src` - Unicode char
+/// * `encoding_table` - table for encoding in SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_char_lossy;
+/// use oem_cp::code_table::{ENCODING_TABLE_CP437, ENCODING_TABLE_CP737};
+/// assert_eq!(encode_char_lossy('π', &ENCODING_TABLE_CP437), 0xE3);
+/// // Archimedes in Greek
+/// assert_eq!(encode_char_lossy('Α', &ENCODING_TABLE_CP737), 0x80);
+/// // Japanese characters are not defined in CP437 and replaced with `?` (0x3F)
+/// assert_eq!(encode_char_lossy('日', &ENCODING_TABLE_CP437), 0x3F);
+/// ```
+pub fn encode_char_lossy(src: char, encoding_table: &OEMCPHashMap<char, u8>) -> u8 {
+    encode_char_checked(src, encoding_table).unwrap_or(b'?')
+}
+
+/// Encode only the char at `index` (in chars, not bytes) of a Unicode string in SBCS (single byte character set)
+///
+/// Since each char is a byte in SBCS, the result is the byte at `index` of the encoded string, without encoding the whole string.
+/// Undefined codepoints are replaced with `0x3F` (`?`).
+/// Returns `None` if `index` is out of range.
+///
+/// # Arguments
+///
+/// * `src` - Unicode string
+/// * `encoding_table` - table for encoding in SBCS
+/// * `index` - index of the char
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::byte_for_char_index;
+/// use oem_cp::code_table::ENCODING_TABLE_CP437;
+///
+/// assert_eq!(byte_for_char_index("1÷½", &ENCODING_TABLE_CP437, 2), Some(0xAB));
+/// assert_eq!(byte_for_char_index("1日", &ENCODING_TABLE_CP437, 1), Some(b'?'));
+/// assert_eq!(byte_for_char_index("1÷½", &ENCODING_TABLE_CP437, 3), None);
+/// ```
+pub fn byte_for_char_index(
+    src: &str,
+    encoding_table: &OEMCPHashMap<char, u8>,
+    index: usize,
+) -> Option<u8> {
+    src.chars()
+        .nth(index)
+        .map(|c| encode_char_lossy(c, encoding_table))
+}
+
+/// Find all bytes that decode to a Unicode char in SBCS (single byte character set)
+///
+/// Bytes are yielded in ascending order.
+/// [`encode_char_checked`] returns the first of them since the lowest byte wins when a code page has duplicate chars.
+///
+/// # Arguments
+///
+/// * `c` - Unicode char
+/// * `table` - table for decoding SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_char_all;
+/// use oem_cp::code_table::CP437_TABLE;
+///
+/// assert!(encode_char_all('π', &CP437_TABLE).eq([0xE3]));
+/// assert!(encode_char_all('A', &CP437_TABLE).eq([b'A']));
+/// assert_eq!(encode_char_all('日', &CP437_TABLE).next(), None);
+/// ```
+pub fn encode_char_all(c: char, table: &TableType) -> impl Iterator<Item = u8> + '_ {
+    (0..=255u8).filter(move |&byte| table.decode_char_checked(byte) == Some(c))
+}
+
+/// Encode Unicode char in the first SBCS (single byte character set) that can represent it
+///
+/// Code pages are tried in the order of `code_pages`, and ones unsupported by this crate (e.g. 1252) are skipped.
+/// Returns the code page ID and the byte, or `None` if no code pages can represent `src`.
+///
+/// # Arguments
+///
+/// * `src` - Unicode char
+/// * `code_pages` - code page IDs to try
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_char_multi;
+///
+/// // 'Ø' is undefined in CP437
+/// assert_eq!(encode_char_multi('Ø', &[437, 850]), Some((850, 0x9D)));
+/// assert_eq!(encode_char_multi('√', &[437, 850]), Some((437, 0xFB)));
+/// assert_eq!(encode_char_multi('A', &[1252, 437]), Some((437, b'A')));
+/// assert_eq!(encode_char_multi('日', &[437, 850]), None);
+/// ```
+pub fn encode_char_multi(src: char, code_pages: &[u16]) -> Option<(u16, u8)> {
+    code_pages.iter().find_map(|code_page| {
+        let encoding_table = ENCODING_TABLE_CP_MAP.get(code_page)?;
+        encode_char_checked(src, encoding_table).map(|byte| (*code_page, byte))
+    })
+}
+
+/// Drop a leading UTF-8 BOM (`EF BB BF`) from bytes
+///
+/// Returns `bytes` as is if they don't start with the BOM.
+/// Useful before decoding files that may have been saved as UTF-8 by mistake.
+///
+/// # Arguments
+///
+/// * `bytes` - bytes that may start with a UTF-8 BOM
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::strip_bom;
+///
+/// assert_eq!(strip_bom(b"\xEF\xBB\xBFabc"), b"abc");
+/// assert_eq!(strip_bom(&[0xFB, 0xAC]), [0xFB, 0xAC]);
+/// ```
+pub fn strip_bom(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes)
+}
+
+/// Rewrite each byte in place
+///
+/// Building block for same-length transforms such as [`make_uppercase_in_page`](crate::make_uppercase_in_page) and [`recode_in_place`].
+///
+/// # Arguments
+///
+/// * `bytes` - bytes to be rewritten
+/// * `f` - function that maps each byte
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::map_bytes_in_place;
+///
+/// let mut bytes = *b"a-b_c";
+/// map_bytes_in_place(&mut bytes, |byte| if byte == b'_' { b'-' } else { byte });
+/// assert_eq!(&bytes, b"a-b-c");
+/// ```
+pub fn map_bytes_in_place<F: FnMut(u8) -> u8>(bytes: &mut [u8], mut f: F) {
+    for byte in bytes {
+        *byte = f(*byte);
+    }
+}
+
+/// Re-encode bytes in SBCS (single byte character set) into another SBCS in place
+///
+/// One byte is always re-encoded to one byte between SBCSs, so nothing is allocated.
+/// Bytes undefined in `from` or whose chars aren't representable in `to` are replaced with `replacement`.
+/// Returns `true` if all bytes are re-encoded cleanly.
+/// If either code page is unsupported, `bytes` are left untouched and `false` is returned.
+///
+/// # Arguments
+///
+/// * `bytes` - bytes encoded in the code page `from`
+/// * `from` - code page ID of `bytes`
+/// * `to` - code page ID to re-encode `bytes` into
+/// * `replacement` - byte used in place of bytes that can't be re-encoded
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::recode_in_place;
+///
+/// // "Öl" in CP850 => CP852
+/// let mut bytes = [0x99, b'l'];
+/// assert!(recode_in_place(&mut bytes, 850, 852, b'?'));
+/// assert_eq!(bytes, [0x99, b'l']);
+/// // "à" isn't representable in CP852
+/// let mut bytes = [0x85, b'!'];
+/// assert!(!recode_in_place(&mut bytes, 850, 852, b'?'));
+/// assert_eq!(&bytes, b"?!");
+/// ```
+pub fn recode_in_place(bytes: &mut [u8], from: u16, to: u16, replacement: u8) -> bool {
+    let (Some(decoding_table), Some(encoding_table)) = (
+        DECODING_TABLE_CP_MAP.get(&from),
+        ENCODING_TABLE_CP_MAP.get(&to),
+    ) else {
+        return false;
+    };
+    let mut clean = true;
+    map_bytes_in_place(bytes, |byte| {
+        let recoded = decoding_table
+            .decode_char_checked(byte)
+            .and_then(|c| encode_char_checked(c, encoding_table));
+        clean &= recoded.is_some();
+        recoded.unwrap_or(replacement)
+    });
+    clean
+}
+
+/// Encode Unicode string in SBCS (single byte character set) into a callback without allocation
+///
+/// `sink` is called for each encoded byte in order.
+/// Undefined codepoints are replaced with `replacement`.
+///
+/// # Arguments
+///
+/// * `src` - Unicode string
+/// * `encoding_table` - table for encoding in SBCS
+/// * `sink` - callback receiving encoded bytes
+/// * `replacement` - byte used in place of undefined codepoints
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_to_sink;
+/// use oem_cp::code_table::ENCODING_TABLE_CP437;
+///
+/// let mut fifo = Vec::new();
+/// encode_to_sink("π≈日", &ENCODING_TABLE_CP437, |byte| fifo.push(byte), b'*');
+/// assert_eq!(fifo, [0xE3, 0xF7, b'*']);
+/// ```
+pub fn encode_to_sink<F: FnMut(u8)>(
+    src: &str,
+    encoding_table: &OEMCPHashMap<char, u8>,
+    mut sink: F,
+    replacement: u8,
+) {
+    for c in src.chars() {
+        sink(encode_char_checked(c, encoding_table).unwrap_or(replacement));
+    }
+}
+
+/// Decode SBCS (single byte character set) bytes lazily without allocation
+///
+/// Undefined codepoints are replaced with U+FFFD.
+///
+/// # Arguments
+///
+/// * `src` - bytes encoded in SBCS
+/// * `table` - table for decoding SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::decode_iter;
+/// use oem_cp::code_table::CP437_TABLE;
+///
+/// let mut decoded = decode_iter(&[0xFB, 0x32, 0xAB], &CP437_TABLE);
+/// assert_eq!(decoded.next(), Some('√'));
+/// assert_eq!(decoded.next(), Some('2'));
+/// assert_eq!(decoded.next(), Some('½'));
+/// assert_eq!(decoded.next(), None);
+/// ```
+pub fn decode_iter<'a>(
+    src: &'a [u8],
+    table: &'static TableType,
+) -> impl Iterator<Item = char> + 'a {
+    src.iter().map(move |byte| table.decode_char_lossy(*byte))
+}
+
+/// Decode SBCS (single byte character set) bytes lazily without allocation, stopping at the first undefined codepoint
+///
+/// Yields `Err` for the first undefined byte and then ends, so `collect`ing into `Result` or `try_fold` can bail early.
+///
+/// # Arguments
+///
+/// * `src` - bytes encoded in SBCS
+/// * `table` - table for decoding SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{decode_iter_checked, TryFromU8Error};
+/// use oem_cp::code_table::CP874_TABLE;
+///
+/// let mut decoded = decode_iter_checked(&[0xA1, 0xDB, 0xA2], &CP874_TABLE);
+/// assert_eq!(decoded.next(), Some(Ok('\u{E01}')));
+/// // 0xDB is undefined in CP874
+/// assert_eq!(decoded.next(), Some(Err(TryFromU8Error)));
+/// assert_eq!(decoded.next(), None);
+/// ```
+pub fn decode_iter_checked<'a>(
+    src: &'a [u8],
+    table: &'static TableType,
+) -> impl Iterator<Item = Result<char, TryFromU8Error>> + 'a {
+    let mut bytes = src.iter();
+    core::iter::from_fn(move || {
+        let byte = bytes.next()?;
+        let decoded = table.decode_char_checked(*byte).ok_or(TryFromU8Error);
+        if decoded.is_err() {
+            bytes = [].iter();
+        }
+        Some(decoded)
+    })
+}
+
+/// Encode Unicode chars in SBCS (single byte character set) lazily
+///
+/// Yields `Some(byte)` for each char, or `None` if it is undefined in the code page, so the caller can choose how to replace it.
+///
+/// # Arguments
+///
+/// * `src` - Unicode chars
+/// * `encoding_table` - table for encoding in SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_chars_iter;
+/// use oem_cp::code_table::ENCODING_TABLE_CP437;
+///
+/// let mut encoded = encode_chars_iter("π≈3日".chars(), &ENCODING_TABLE_CP437);
+/// assert_eq!(encoded.next(), Some(Some(0xE3)));
+/// assert_eq!(encoded.next(), Some(Some(0xF7)));
+/// assert_eq!(encoded.next(), Some(Some(0x33)));
+/// // Japanese characters are not defined in CP437
+/// assert_eq!(encoded.next(), Some(None));
+/// assert_eq!(encoded.next(), None);
+/// ```
+pub fn encode_chars_iter<'a, I>(
+    src: I,
+    encoding_table: &'a OEMCPHashMap<char, u8>,
+) -> impl Iterator<Item = Option<u8>> + 'a
+where
+    I: IntoIterator<Item = char>,
+    I::IntoIter: 'a,
+{
+    src.into_iter()
+        .map(move |c| encode_char_checked(c, encoding_table))
+}
+
+/// Encode Unicode string in SBCS (single byte character set) lazily without allocation
+///
+/// Undefined codepoints are replaced with `0x3F` (`?`).
+/// ASCII chars are passed through without looking up `encoding_table`.
+///
+/// # Arguments
+///
+/// * `src` - Unicode string
+/// * `encoding_table` - table for encoding in SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_iter;
+/// use oem_cp::code_table::ENCODING_TABLE_CP437;
+///
+/// let mut uart = Vec::new();
+/// for byte in encode_iter("π≈3日", &ENCODING_TABLE_CP437) {
+///     uart.push(byte);
+/// }
+/// assert_eq!(uart, [0xE3, 0xF7, b'3', b'?']);
+/// ```
+pub fn encode_iter<'a>(
+    src: &'a str,
+    encoding_table: &'a OEMCPHashMap<char, u8>,
+) -> impl Iterator<Item = u8> + 'a {
+    src.chars()
+        .map(move |c| encode_char_lossy(c, encoding_table))
+}
+
+/// Encode Unicode string in SBCS (single byte character set) lazily without allocation, stopping at the first undefined codepoint
+///
+/// Yields `Err` for the first char undefined in the code page and then ends, so `collect`ing into `Result` or `try_fold` can bail early.
+/// ASCII chars are passed through without looking up `encoding_table`.
+///
+/// # Arguments
+///
+/// * `src` - Unicode string
+/// * `encoding_table` - table for encoding in SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{encode_iter_checked, TryFromCharError};
+/// use oem_cp::code_table::ENCODING_TABLE_CP437;
+///
+/// let mut encoded = encode_iter_checked("π日3", &ENCODING_TABLE_CP437);
+/// assert_eq!(encoded.next(), Some(Ok(0xE3)));
+/// // Japanese characters are not defined in CP437
+/// assert_eq!(encoded.next(), Some(Err(TryFromCharError)));
+/// assert_eq!(encoded.next(), None);
+/// ```
+pub fn encode_iter_checked<'a>(
+    src: &'a str,
+    encoding_table: &'a OEMCPHashMap<char, u8>,
+) -> impl Iterator<Item = Result<u8, TryFromCharError>> + 'a {
+    let mut chars = src.chars();
+    core::iter::from_fn(move || {
+        let c = chars.next()?;
+        let encoded = encode_char_checked(c, encoding_table).ok_or(TryFromCharError);
+        if encoded.is_err() {
+            chars = "".chars();
+        }
+        Some(encoded)
+    })
+}
+
+/// Encode Unicode string in SBCS (single byte character set) into an `ArrayVec` without allocation
+///
+/// Undefined codepoints are replaced with `0x3F` (`?`).
+/// Each char is encoded to exactly one byte, so this function fails if `src` has more than `N` chars.
+///
+/// # Arguments
+///
+/// * `src` - Unicode string
+/// * `encoding_table` - table for encoding in SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::encode_string_lossy_array;
+/// use oem_cp::code_table::ENCODING_TABLE_CP437;
+///
+/// let encoded = encode_string_lossy_array::<8>("π≈22/7", &ENCODING_TABLE_CP437).unwrap();
+/// assert_eq!(&encoded[..], &[0xE3, 0xF7, 0x32, 0x32, 0x2F, 0x37]);
+/// assert!(encode_string_lossy_array::<4>("π≈22/7", &ENCODING_TABLE_CP437).is_err());
+/// ```
+#[cfg(feature = "arrayvec")]
+pub fn encode_string_lossy_array<const N: usize>(
+    src: &str,
+    encoding_table: &OEMCPHashMap<char, u8>,
+) -> Result<arrayvec::ArrayVec<u8, N>, arrayvec::CapacityError> {
+    let mut ret = arrayvec::ArrayVec::new();
+    for c in src.chars() {
+        ret.try_push(encode_char_lossy(c, encoding_table))
+            .map_err(|error| error.simplify())?;
+    }
+    Ok(ret)
+}
+
+/// Encode Unicode string in SBCS (single byte character set) into uninitialized memory
+///
+/// Undefined codepoints are replaced with `0x3F` (`?`).
+/// Returns the number of bytes written, i.e. `out[..len]` is initialized (the rest is untouched).
+/// Returns `Err` with the needed length (the number of chars in `src`) if `out` is too small; `out` may be partially written then.
+///
+/// This function itself is safe because it only writes to `out`.
+/// Reading `out[..len]` as `&[u8]` requires `unsafe`, which is sound only for the returned `len`.
+///
+/// # Arguments
+///
+/// * `src` - Unicode string
+/// * `encoding_table` - table for encoding in SBCS
+/// * `out` - buffer to write the encoded bytes into
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+/// use oem_cp::encode_str_to_uninit;
+/// use oem_cp::code_table::ENCODING_TABLE_CP437;
+///
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 8];
+/// let len = encode_str_to_uninit("π≈22/7", &ENCODING_TABLE_CP437, &mut buf).unwrap();
+/// // SAFETY: `encode_str_to_uninit` has initialized `buf[..len]`
+/// let encoded = unsafe { std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), len) };
+/// assert_eq!(encoded, [0xE3, 0xF7, 0x32, 0x32, 0x2F, 0x37]);
+/// assert_eq!(encode_str_to_uninit("π≈22/7", &ENCODING_TABLE_CP437, &mut buf[..4]), Err(6));
+/// ```
+pub fn encode_str_to_uninit(
+    src: &str,
+    encoding_table: &OEMCPHashMap<char, u8>,
+    out: &mut [MaybeUninit<u8>],
+) -> Result<usize, usize> {
+    let mut len = 0;
+    for c in src.chars() {
+        let Some(dst) = out.get_mut(len) else {
+            return Err(src.chars().count());
+        };
+        dst.write(encode_char_lossy(c, encoding_table));
+        len += 1;
+    }
+    Ok(len)
+}
+
+/// Check if a byte is defined in SBCS (single byte character set) using a bitmap
+///
+/// ASCII bytes (0x00-0x7F) are always defined.
+///
+/// # Arguments
+///
+/// * `src` - single byte encoded in SBCS
+/// * `bitmap` - bitmap of defined codepoints (`DEFINED_BITMAP_CP###`; bit `n` stands for `0x80 + n`)
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::is_valid_byte_fast;
+/// use oem_cp::code_table::{DEFINED_BITMAP_CP437, DEFINED_BITMAP_CP874};
+///
+/// assert!(is_valid_byte_fast(0xFB, DEFINED_BITMAP_CP437));
+/// assert!(is_valid_byte_fast(0xE9, DEFINED_BITMAP_CP874));
+/// // 0xDB-0xDE,0xFC-0xFF is invalid in CP874 in Windows
+/// assert!(!is_valid_byte_fast(0xDB, DEFINED_BITMAP_CP874));
+/// ```
+pub const fn is_valid_byte_fast(src: u8, bitmap: u128) -> bool {
+    // Trait methods can't be called in `const fn`, so `AsciiFast` is inlined here
+    src < 128 || (bitmap >> (src & 127)) & 1 != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_table::{CP437_TABLE, CP874_TABLE, ENCODING_TABLE_CP437, ENCODING_TABLE_CP874};
+
+    #[test]
+    fn sink_test() {
+        let bytes: [u8; 256] = core::array::from_fn(|i| i as u8);
+        for (code_page, table) in DECODING_TABLE_CP_MAP.entries() {
+            let encoding_table = ENCODING_TABLE_CP_MAP[code_page];
+            let mut decoded = ['\0'; 256];
+            let mut len = 0;
+            table.decode_to_sink(&bytes, |c| {
+                decoded[len] = c;
+                len += 1;
+            });
+            assert_eq!(len, 256);
+            for (byte, c) in bytes.into_iter().zip(decoded) {
+                assert_eq!(c, table.decode_char_lossy(byte));
+                let mut encoded = None;
+                encode_to_sink(
+                    c.encode_utf8(&mut [0; 4]),
+                    encoding_table,
+                    |byte| assert!(encoded.replace(byte).is_none()),
+                    b'?',
+                );
+                assert_eq!(encoded, Some(encode_char_lossy(c, encoding_table)));
+            }
+        }
+        let mut fifo = [0u8; 4];
+        let mut len = 0;
+        encode_to_sink(
+            "π≈日",
+            &ENCODING_TABLE_CP437,
+            |byte| {
+                fifo[len] = byte;
+                len += 1;
+            },
+            b'*',
+        );
+        assert_eq!(fifo[..len], [0xE3, 0xF7, b'*']);
+        let mut count = 0;
+        CP437_TABLE.decode_to_sink(&[], |_| count += 1);
+        encode_to_sink("", &ENCODING_TABLE_CP437, |_| count += 1, b'?');
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn decode_iter_test() {
+        let bytes: [u8; 256] = core::array::from_fn(|i| i as u8);
+        assert!(decode_iter(&bytes, &CP874_TABLE).eq(bytes
+            .iter()
+            .map(|byte| CP874_TABLE.decode_char_lossy(*byte))));
+        assert!(decode_iter_checked(&[b'a', 0xA1, 0xA2], &CP874_TABLE).eq([
+            Ok('a'),
+            Ok('\u{E01}'),
+            Ok('\u{E02}')
+        ]));
+        let mut buf = ['\0'; 4];
+        let len = decode_iter_checked(&[0xA1, 0xA2, 0xDB, 0xA3], &CP874_TABLE)
+            .zip(&mut buf)
+            .try_fold(0, |len, (c, slot)| {
+                *slot = c?;
+                Ok::<_, TryFromU8Error>(len + 1)
+            });
+        assert_eq!(len, Err(TryFromU8Error));
+        assert_eq!(buf, ['\u{E01}', '\u{E02}', '\0', '\0']);
+        assert!(decode_iter_checked(&[0xDB, 0xA1, 0xDC], &CP874_TABLE).eq([Err(TryFromU8Error)]));
+    }
+
+    #[test]
+    fn encode_iter_test() {
+        assert!(encode_iter("½+¼=¾ 日本", &ENCODING_TABLE_CP437).eq(*b"\xAB+\xAC=? ??"));
+        assert!(
+            encode_iter_checked("a\u{E01}\u{E02}", &ENCODING_TABLE_CP874).eq([
+                Ok(b'a'),
+                Ok(0xA1),
+                Ok(0xA2)
+            ])
+        );
+        let ascii: [u8; 128] = core::array::from_fn(|i| i as u8);
+        let ascii = core::str::from_utf8(&ascii).unwrap();
+        assert!(encode_iter(ascii, &ENCODING_TABLE_CP874).eq(0..128));
+        assert!(encode_iter_checked("a日½本", &ENCODING_TABLE_CP437)
+            .eq([Ok(b'a'), Err(TryFromCharError)]));
+    }
+}
@@ -0,0 +1,870 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+use super::code_table::*;
+use super::code_table_type::TableType;
+use super::OEMCPHashMap;
+#[cfg(feature = "alloc")]
+use super::{
+    encode_char_lossy, encode_string_lossy, encode_string_no_controls, unencodable_chars,
+    ControlSet, DecodeError, EncodeError,
+};
+
+include!(concat!(env!("OUT_DIR"), "/codepage.rs"));
+
+impl Codepage {
+    /// Returns the code page ID (e.g. `437` for CP437)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert_eq!(Codepage::Cp874.id(), 874);
+    /// ```
+    pub const fn id(self) -> u16 {
+        self as u16
+    }
+
+    /// Returns all the supported code pages in ascending order of ID
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert_eq!(Codepage::all()[0], Codepage::Cp437);
+    /// assert!(Codepage::all().contains(&Codepage::Cp874));
+    /// ```
+    pub fn all() -> &'static [Codepage] {
+        &Self::ALL
+    }
+
+    /// Iterates over all the supported code pages in ascending order of ID
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// for codepage in Codepage::iter() {
+    ///     println!("{}: {}", codepage.id(), codepage.name());
+    /// }
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Codepage> {
+        Self::ALL.iter().copied()
+    }
+
+    /// Checks if bytes 0x00-0x7F are decoded to the same codepoints (U+0000-U+007F) in the code page
+    ///
+    /// All the supported code pages are ASCII compatible for now.
+    /// See [`TableType::is_ascii_compatible`](crate::code_table_type::TableType::is_ascii_compatible).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert!(Codepage::Cp437.is_ascii_compatible());
+    /// ```
+    pub fn is_ascii_compatible(&self) -> bool {
+        self.decoding().is_ascii_compatible()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Codepage {
+    /// Decodes bytes encoded in the code page into `out`, reusing its allocation
+    ///
+    /// `out` is cleared first, so it holds only the decoded string afterwards.
+    /// Undefined codepoints are replaced with U+FFFD.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - bytes encoded in the code page
+    /// * `out` - buffer to store the decoded string in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// let mut buf = String::new();
+    /// Codepage::Cp437.decode_into(&[0xFB, 0xAC, 0x3D, 0xAB], &mut buf);
+    /// assert_eq!(buf, "√¼=½");
+    /// // 0xDB is undefined in CP874
+    /// Codepage::Cp874.decode_into(&[0x30, 0xDB], &mut buf);
+    /// assert_eq!(buf, "0\u{FFFD}");
+    /// ```
+    pub fn decode_into(&self, src: &[u8], out: &mut String) {
+        let table = self.decoding();
+        out.clear();
+        out.reserve(src.len());
+        out.extend(src.iter().map(|&byte| table.decode_char_lossy(byte)));
+    }
+
+    /// Encodes a Unicode string in the code page into `out`, reusing its allocation
+    ///
+    /// `out` is cleared first, so it holds only the encoded bytes afterwards.
+    /// Undefined codepoints are replaced with `0x3F` (`?`).
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - Unicode string
+    /// * `out` - buffer to store the encoded bytes in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// let mut buf = Vec::new();
+    /// Codepage::Cp437.encode_into("π≈22/7", &mut buf);
+    /// assert_eq!(buf, [0xE3, 0xF7, 0x32, 0x32, 0x2F, 0x37]);
+    /// Codepage::Cp437.encode_into("日本", &mut buf);
+    /// assert_eq!(buf, b"??");
+    /// ```
+    pub fn encode_into(&self, src: &str, out: &mut Vec<u8>) {
+        let table = self.encoding();
+        out.clear();
+        out.reserve(src.len());
+        out.extend(src.chars().map(|c| encode_char_lossy(c, table)));
+    }
+
+    /// Encodes a Unicode string in the code page, failing on unrepresentable characters
+    ///
+    /// The error carries the code page ID, the character, and its index in `src.chars()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - Unicode string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert_eq!(Codepage::Cp437.encode_checked("π≈22/7"), Ok(vec![0xE3, 0xF7, 0x32, 0x32, 0x2F, 0x37]));
+    /// let error = Codepage::Cp437.encode_checked("10 €").unwrap_err();
+    /// assert_eq!((error.code_page(), error.char(), error.position()), (Some(437), '€', 3));
+    /// assert_eq!(error.to_string(), "can't encode '€' (U+20AC) at position 3 in CP437");
+    /// ```
+    pub fn encode_checked(&self, src: &str) -> Result<Vec<u8>, EncodeError> {
+        encode_string_no_controls(src, self.encoding(), ControlSet::NONE)
+            .map_err(|error| error.with_code_page(self.id()))
+    }
+
+    /// Encodes a Unicode string in the candidate code page that loses the fewest characters
+    ///
+    /// Each candidate is scored by the number of distinct characters of `s` it can't represent (see [`unencodable_chars`]).
+    /// Ties are broken by the order of `candidates`, and ones unsupported by this crate are ignored.
+    /// Returns the chosen code page ID and the lossily encoded bytes, or `None` if no candidates remain.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - Unicode string
+    /// * `candidates` - code page IDs to try
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// // 'Ø' is undefined in CP437
+    /// assert_eq!(Codepage::best_for_string("Øl", &[437, 850]), Some((850, vec![0x9D, b'l'])));
+    /// assert_eq!(Codepage::best_for_string("日", &[932, 437, 850]), Some((437, vec![b'?'])));
+    /// assert_eq!(Codepage::best_for_string("abc", &[]), None);
+    /// ```
+    pub fn best_for_string(s: &str, candidates: &[u16]) -> Option<(u16, Vec<u8>)> {
+        let mut best: Option<(u16, &OEMCPHashMap<char, u8>, usize)> = None;
+        for &code_page in candidates {
+            let Some(table) = ENCODING_TABLE_CP_MAP.get(&code_page) else {
+                continue;
+            };
+            let losses = unencodable_chars(s, table).len();
+            if best.map_or(true, |(_, _, best_losses)| losses < best_losses) {
+                best = Some((code_page, table, losses));
+            }
+        }
+        best.map(|(code_page, table, _)| (code_page, encode_string_lossy(s, table)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Codepage {
+    /// Reads a file encoded in the code page and decodes it
+    ///
+    /// The file is read in chunks through a buffer, so the raw bytes are never held in memory at once.
+    /// Decoding is lossy: undefined codepoints are replaced with U+FFFD instead of failing.
+    /// Only I/O errors are returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the file encoded in the code page
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use oem_cp::Codepage;
+    ///
+    /// let report = Codepage::Cp850.decode_file("report.txt")?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn decode_file<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<String> {
+        use std::io::Read;
+
+        let mut file = std::fs::File::open(path)?;
+        let table = self.decoding();
+        let mut ret = String::new();
+        let mut buf = [0; 8192];
+        loop {
+            let len = match file.read(&mut buf) {
+                Ok(0) => return Ok(ret),
+                Ok(len) => len,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            table.decode_to_sink(&buf[..len], |c| ret.push(c));
+        }
+    }
+
+    /// Encodes a Unicode string in the code page and writes it to a file
+    ///
+    /// The file is created or truncated, and the encoded bytes are written in chunks through a buffer.
+    /// Encoding is lossy: characters not representable in the code page are replaced with `0x3F` (`?`) instead of failing.
+    /// Only I/O errors are returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the file to write
+    /// * `src` - Unicode string
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use oem_cp::Codepage;
+    ///
+    /// Codepage::Cp850.encode_file("report.txt", "Öl: 5 €")?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn encode_file<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        src: &str,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut file = std::fs::File::create(path)?;
+        let table = self.encoding();
+        let mut buf = Vec::with_capacity(8192);
+        for c in src.chars() {
+            buf.push(encode_char_lossy(c, table));
+            if buf.len() == buf.capacity() {
+                file.write_all(&buf)?;
+                buf.clear();
+            }
+        }
+        file.write_all(&buf)?;
+        file.flush()
+    }
+}
+
+/// Error returned when a code page ID isn't supported by this crate
+///
+/// It keeps the ID for diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnsupportedCodepage(pub u16);
+
+impl fmt::Display for UnsupportedCodepage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported code page: {}", self.0)
+    }
+}
+
+impl core::error::Error for UnsupportedCodepage {}
+
+/// Same as [`Codepage::from_id`], but returns an error instead of `None`
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use oem_cp::{Codepage, UnsupportedCodepage};
+///
+/// assert_eq!(Codepage::try_from(437), Ok(Codepage::Cp437));
+/// // CP932 (Shift-JIS; Japanese MBCS) is unsupported
+/// assert_eq!(Codepage::try_from(932), Err(UnsupportedCodepage(932)));
+/// ```
+impl TryFrom<u16> for Codepage {
+    type Error = UnsupportedCodepage;
+
+    fn try_from(id: u16) -> Result<Self, Self::Error> {
+        Self::from_id(id).ok_or(UnsupportedCodepage(id))
+    }
+}
+
+/// Compares the code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::Codepage;
+///
+/// assert!(Codepage::Cp437 == 437);
+/// assert!(437 == Codepage::Cp437);
+/// assert!(Codepage::Cp437 != 850);
+/// ```
+impl PartialEq<u16> for Codepage {
+    fn eq(&self, other: &u16) -> bool {
+        self.id() == *other
+    }
+}
+
+impl PartialEq<Codepage> for u16 {
+    fn eq(&self, other: &Codepage) -> bool {
+        *self == other.id()
+    }
+}
+
+/// Error returned when a string isn't recognized as a supported code page
+///
+/// It keeps the input for diagnostics.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownEncoding(pub String);
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for UnknownEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown or unsupported encoding: {:?}", self.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for UnknownEncoding {}
+
+impl Codepage {
+    /// Parses code page labels such as `"437"`, `"CP437"`, `"cp-874"`, `"IBM437"`, and `"windows-874"`
+    ///
+    /// Case and separators (`-`, `_`, `.`, and spaces) are ignored, and a leading `cp`, `ibm`, `windows`, `oem`, or `dos` is stripped before the code page ID is parsed.
+    /// Labels that aren't numeric after stripping the prefix, such as `"OEM-US"` and `"TIS-620"`, are looked up in [`Codepage::alias_names`] with the same normalization.
+    /// Returns `None` if the label isn't recognized.
+    /// Unlike `FromStr`, it's available without the `alloc` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Codepage;
+    ///
+    /// assert_eq!(Codepage::from_label("IBM437"), Some(Codepage::Cp437));
+    /// assert_eq!(Codepage::from_label("OEM-US"), Some(Codepage::Cp437));
+    /// assert_eq!(Codepage::from_label("utf-8"), None);
+    /// ```
+    pub fn from_label(label: &str) -> Option<Self> {
+        const PREFIXES: [&[u8]; 5] = [b"windows", b"ibm", b"oem", b"dos", b"cp"];
+
+        let mut buf = [0u8; 32];
+        let mut len = 0;
+        for byte in normalize_label(label) {
+            *buf.get_mut(len)? = byte;
+            len += 1;
+        }
+        let normalized = &buf[..len];
+        let id = PREFIXES
+            .iter()
+            .find(|prefix| normalized.starts_with(prefix))
+            .map_or(normalized, |prefix| &normalized[prefix.len()..]);
+        if !id.is_empty() && id.iter().all(u8::is_ascii_digit) {
+            return core::str::from_utf8(id)
+                .ok()?
+                .parse()
+                .ok()
+                .and_then(Self::from_id);
+        }
+        Self::iter().find(|codepage| {
+            codepage
+                .alias_names()
+                .iter()
+                .any(|alias| normalize_label(alias).eq(normalized.iter().copied()))
+        })
+    }
+}
+
+/// Removes separators (`-`, `_`, `.`, and spaces) from a label and lowercases it
+fn normalize_label(label: &str) -> impl Iterator<Item = u8> + '_ {
+    label
+        .bytes()
+        .filter(|byte| !b"-_. ".contains(byte))
+        .map(|byte| byte.to_ascii_lowercase())
+}
+
+/// Parses code page labels in the same way as [`Codepage::from_label`]
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::Codepage;
+///
+/// assert_eq!("437".parse(), Ok(Codepage::Cp437));
+/// assert_eq!("IBM437".parse(), Ok(Codepage::Cp437));
+/// assert_eq!("windows-874".parse(), Ok(Codepage::Cp874));
+/// assert_eq!("cp-874".parse(), Ok(Codepage::Cp874));
+/// // CP932 (Shift-JIS; Japanese MBCS) is unsupported
+/// assert!("cp932".parse::<Codepage>().is_err());
+/// ```
+#[cfg(feature = "alloc")]
+impl core::str::FromStr for Codepage {
+    type Err = UnknownEncoding;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_label(s).ok_or_else(|| UnknownEncoding(s.into()))
+    }
+}
+
+/// Same as [`str::parse`] (see the `FromStr` implementation)
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use oem_cp::Codepage;
+///
+/// assert_eq!(Codepage::try_from("CP850"), Ok(Codepage::Cp850));
+/// assert_eq!(Codepage::try_from("utf-8").unwrap_err().0, "utf-8");
+/// ```
+#[cfg(feature = "alloc")]
+impl TryFrom<&str> for Codepage {
+    type Error = UnknownEncoding;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Error returned by [`decode_by_label`] and [`decode_by_label_checked`]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeByLabelError {
+    /// The label isn't recognized as a supported code page
+    UnknownLabel(UnknownEncoding),
+    /// The bytes contain an undefined codepoint (only from the checked variant)
+    UndefinedByte(DecodeError),
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for DecodeByLabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLabel(e) => fmt::Display::fmt(e, f),
+            Self::UndefinedByte(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for DecodeByLabelError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::UnknownLabel(e) => Some(e),
+            Self::UndefinedByte(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<UnknownEncoding> for DecodeByLabelError {
+    fn from(e: UnknownEncoding) -> Self {
+        Self::UnknownLabel(e)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<DecodeError> for DecodeByLabelError {
+    fn from(e: DecodeError) -> Self {
+        Self::UndefinedByte(e)
+    }
+}
+
+/// Error returned by [`encode_by_label`] and [`encode_by_label_checked`]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeByLabelError {
+    /// The label isn't recognized as a supported code page
+    UnknownLabel(UnknownEncoding),
+    /// The string contains a character not representable in the code page (only from the checked variant)
+    Unencodable(EncodeError),
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for EncodeByLabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLabel(e) => fmt::Display::fmt(e, f),
+            Self::Unencodable(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for EncodeByLabelError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::UnknownLabel(e) => Some(e),
+            Self::Unencodable(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<UnknownEncoding> for EncodeByLabelError {
+    fn from(e: UnknownEncoding) -> Self {
+        Self::UnknownLabel(e)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<EncodeError> for EncodeByLabelError {
+    fn from(e: EncodeError) -> Self {
+        Self::Unencodable(e)
+    }
+}
+
+/// Decode bytes in the code page named by `label`
+///
+/// `label` is parsed in the same way as `Codepage`'s `FromStr` (see [`Codepage::from_label`]).
+/// Undefined codepoints are replaced with U+FFFD.
+///
+/// # Arguments
+///
+/// * `label` - code page label (e.g. `"cp437"`, `"IBM850"`)
+/// * `bytes` - bytes encoded in the code page
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{decode_by_label, DecodeByLabelError};
+///
+/// assert_eq!(decode_by_label("IBM437", &[0xFB, 0xAC, 0x3D, 0xAB]), Ok("√¼=½".to_string()));
+/// assert_eq!(decode_by_label("windows-874", &[0x30, 0xDB]), Ok("0\u{FFFD}".to_string()));
+/// assert!(matches!(decode_by_label("utf-8", b"abc"), Err(DecodeByLabelError::UnknownLabel(_))));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_by_label(label: &str, bytes: &[u8]) -> Result<String, DecodeByLabelError> {
+    let codepage: Codepage = label.parse()?;
+    Ok(codepage.decoding().decode_string_lossy(bytes))
+}
+
+/// Decode bytes in the code page named by `label`, failing on undefined codepoints
+///
+/// Same as [`decode_by_label`], but returns [`DecodeByLabelError::UndefinedByte`] instead of replacing undefined codepoints.
+///
+/// # Arguments
+///
+/// * `label` - code page label (e.g. `"cp437"`, `"IBM850"`)
+/// * `bytes` - bytes encoded in the code page
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{decode_by_label_checked, DecodeByLabelError};
+///
+/// assert_eq!(decode_by_label_checked("cp874", &[0xA1, 0xD8, 0xE9, 0xA7]), Ok("กุ้ง".to_string()));
+/// let Err(DecodeByLabelError::UndefinedByte(e)) = decode_by_label_checked("cp874", &[0x30, 0xDB]) else {
+///     panic!("0xDB must be undefined in CP874");
+/// };
+/// assert_eq!(e.position(), 1);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_by_label_checked(label: &str, bytes: &[u8]) -> Result<String, DecodeByLabelError> {
+    let codepage: Codepage = label.parse()?;
+    Ok(codepage.decoding().decode_cow_checked(bytes)?.into_owned())
+}
+
+/// Encode a Unicode string in the code page named by `label`
+///
+/// `label` is parsed in the same way as `Codepage`'s `FromStr` (see [`Codepage::from_label`]).
+/// Unrepresentable characters are replaced with `0x3F` (`?`).
+///
+/// # Arguments
+///
+/// * `label` - code page label (e.g. `"cp437"`, `"IBM850"`)
+/// * `src` - Unicode string
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{encode_by_label, EncodeByLabelError};
+///
+/// assert_eq!(encode_by_label("IBM437", "π≈22/7"), Ok(vec![0xE3, 0xF7, 0x32, 0x32, 0x2F, 0x37]));
+/// assert_eq!(encode_by_label("cp437", "日本"), Ok(b"??".to_vec()));
+/// assert!(matches!(encode_by_label("utf-8", "abc"), Err(EncodeByLabelError::UnknownLabel(_))));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encode_by_label(label: &str, src: &str) -> Result<Vec<u8>, EncodeByLabelError> {
+    let codepage: Codepage = label.parse()?;
+    Ok(encode_string_lossy(src, codepage.encoding()))
+}
+
+/// Encode a Unicode string in the code page named by `label`, failing on unrepresentable characters
+///
+/// Same as [`encode_by_label`], but returns [`EncodeByLabelError::Unencodable`] instead of replacing unrepresentable characters.
+///
+/// # Arguments
+///
+/// * `label` - code page label (e.g. `"cp437"`, `"IBM850"`)
+/// * `src` - Unicode string
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{encode_by_label_checked, EncodeByLabelError};
+///
+/// assert_eq!(encode_by_label_checked("cp437", "√¼"), Ok(vec![0xFB, 0xAC]));
+/// let Err(EncodeByLabelError::Unencodable(e)) = encode_by_label_checked("cp437", "a日") else {
+///     panic!("'日' must be unrepresentable in CP437");
+/// };
+/// assert_eq!((e.position(), e.char()), (1, '日'));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encode_by_label_checked(label: &str, src: &str) -> Result<Vec<u8>, EncodeByLabelError> {
+    let codepage: Codepage = label.parse()?;
+    Ok(codepage.encode_checked(src)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codepage_coverage_test() {
+        assert_eq!(Codepage::all().len(), DECODING_TABLE_CP_MAP.len());
+        assert!(Codepage::all().windows(2).all(|w| w[0].id() < w[1].id()));
+        for codepage in Codepage::iter() {
+            assert!(DECODING_TABLE_CP_MAP.contains_key(&codepage.id()));
+            assert!(ENCODING_TABLE_CP_MAP.contains_key(&codepage.id()));
+            assert_eq!(Codepage::from_id(codepage.id()), Some(codepage));
+            assert_eq!(codepage, codepage.id());
+            assert_eq!(codepage.id(), codepage);
+            assert_ne!(codepage, 932);
+            assert_eq!(codepage.name(), format!("CP{}", codepage.id()));
+            assert!(codepage.is_ascii_compatible());
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn codepage_into_test() {
+        let mut decoded = String::from("garbage");
+        let mut encoded = Vec::from(*b"garbage");
+        for codepage in Codepage::iter() {
+            let decoding_table = &DECODING_TABLE_CP_MAP[&codepage.id()];
+            let encoding_table = ENCODING_TABLE_CP_MAP[&codepage.id()];
+            let bytes = (0..=255).collect::<Vec<u8>>();
+            codepage.decode_into(&bytes, &mut decoded);
+            assert_eq!(decoded, decoding_table.decode_string_lossy(&bytes));
+            codepage.encode_into(&decoded, &mut encoded);
+            assert_eq!(
+                encoded,
+                crate::encode_string_lossy(&decoded, encoding_table)
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn best_for_string_test() {
+        // cspell: disable
+        assert_eq!(
+            Codepage::best_for_string("Привет", &[437, 866, 855]),
+            Some((
+                866,
+                crate::encode_string_lossy("Привет", &ENCODING_TABLE_CP866)
+            ))
+        );
+        // cspell: enable
+        // Ties are broken by the order of candidates
+        assert_eq!(
+            Codepage::best_for_string("日本", &[850, 437]),
+            Some((850, b"??".to_vec()))
+        );
+        assert_eq!(Codepage::best_for_string("", &[437]), Some((437, vec![])));
+        assert_eq!(Codepage::best_for_string("a", &[932, 1252]), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn codepage_encode_checked_test() {
+        for codepage in Codepage::iter() {
+            let encodable = codepage
+                .decoding()
+                .decode_string_lossy(&(0..=255).collect::<Vec<u8>>())
+                .replace('\u{FFFD}', "");
+            assert_eq!(
+                codepage.encode_checked(&encodable).ok(),
+                crate::encode_string_checked(&encodable, codepage.encoding())
+            );
+            let error = codepage.encode_checked("a日").unwrap_err();
+            assert_eq!(error.code_page(), Some(codepage.id()));
+            assert_eq!(error.kind(), crate::ConversionErrorKind::OutOfRange);
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "can't encode '日' (U+65E5) at position 1 in {}",
+                    codepage.name()
+                )
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn codepage_file_test() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("oem_cp_file_test_{}.txt", std::process::id()));
+        // Longer than the chunk size
+        let text = "Öl: 5 ¤ ½ ".repeat(2000);
+        Codepage::Cp850.encode_file(&path, &text).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(
+            bytes,
+            crate::encode_string_lossy(&text, &ENCODING_TABLE_CP850)
+        );
+        assert_eq!(Codepage::Cp850.decode_file(&path).unwrap(), text);
+        // Lossy in both directions
+        Codepage::Cp874.encode_file(&path, "日").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"?");
+        std::fs::write(&path, [0x30, 0xDB]).unwrap();
+        assert_eq!(Codepage::Cp874.decode_file(&path).unwrap(), "0\u{FFFD}");
+        std::fs::remove_file(&path).unwrap();
+        assert!(Codepage::Cp437.decode_file(&path).is_err());
+    }
+
+    #[test]
+    fn codepage_try_from_u16_test() {
+        for id in 0..=u16::MAX {
+            match Codepage::from_id(id) {
+                Some(codepage) => assert_eq!(Codepage::try_from(id), Ok(codepage)),
+                None => assert_eq!(Codepage::try_from(id), Err(UnsupportedCodepage(id))),
+            }
+        }
+        assert_eq!(
+            UnsupportedCodepage(932).to_string(),
+            "unsupported code page: 932"
+        );
+    }
+
+    #[test]
+    fn codepage_alias_names_test() {
+        for codepage in Codepage::iter() {
+            assert!(!codepage.alias_names().is_empty(), "{codepage:?}");
+            for alias in codepage.alias_names() {
+                assert_eq!(Codepage::from_label(alias), Some(codepage), "{alias}");
+            }
+        }
+        for (label, codepage) in [
+            ("OEM-US", Codepage::Cp437),
+            ("csPC8CodePage437", Codepage::Cp437),
+            ("TIS-620", Codepage::Cp874),
+            ("iso_8859_11", Codepage::Cp874),
+            ("cp-gr", Codepage::Cp869),
+            ("CP-IS", Codepage::Cp861),
+        ] {
+            assert_eq!(Codepage::from_label(label), Some(codepage), "{label}");
+        }
+    }
+
+    #[test]
+    fn codepage_tables_test() {
+        assert!(matches!(
+            Codepage::Cp437.decoding(),
+            TableType::Complete(table) if core::ptr::eq(*table, &DECODING_TABLE_CP437)
+        ));
+        assert!(core::ptr::eq(
+            Codepage::Cp437.encoding(),
+            &ENCODING_TABLE_CP437
+        ));
+        for codepage in Codepage::iter() {
+            let decoding = &DECODING_TABLE_CP_MAP[&codepage.id()];
+            assert_eq!(
+                codepage.decoding().full_mapping(),
+                decoding.full_mapping(),
+                "{codepage:?}"
+            );
+            assert!(
+                core::ptr::eq(codepage.encoding(), ENCODING_TABLE_CP_MAP[&codepage.id()]),
+                "{codepage:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn by_label_test() {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        for codepage in Codepage::iter() {
+            for label in codepage.alias_names() {
+                let decoded = decode_by_label(label, &bytes).unwrap();
+                assert_eq!(decoded, codepage.decoding().decode_string_lossy(&bytes));
+                assert_eq!(
+                    decode_by_label_checked(label, &bytes).ok(),
+                    codepage.decoding().decode_string_checked(&bytes)
+                );
+                assert_eq!(
+                    encode_by_label(label, &decoded).unwrap(),
+                    crate::encode_string_lossy(&decoded, codepage.encoding())
+                );
+                assert_eq!(
+                    encode_by_label_checked(label, &decoded).ok(),
+                    crate::encode_string_checked(&decoded, codepage.encoding())
+                );
+            }
+        }
+        let unknown = UnknownEncoding("utf-8".into());
+        assert_eq!(
+            decode_by_label_checked("utf-8", &bytes),
+            Err(DecodeByLabelError::UnknownLabel(unknown.clone()))
+        );
+        assert_eq!(
+            encode_by_label_checked("utf-8", "abc"),
+            Err(EncodeByLabelError::UnknownLabel(unknown.clone()))
+        );
+        assert_eq!(
+            DecodeByLabelError::from(unknown).to_string(),
+            "unknown or unsupported encoding: \"utf-8\""
+        );
+        let error = encode_by_label_checked("cp437", "ab€").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "can't encode '€' (U+20AC) at position 2 in CP437"
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn codepage_from_str_test() {
+        for label in ["437", "CP437", "cp437", "IBM437", "ibm-437", "OEM 437"] {
+            assert_eq!(label.parse(), Ok(Codepage::Cp437), "{label}");
+        }
+        for label in ["windows-874", "874", "cp874", "CP_874", "cp-874"] {
+            assert_eq!(label.parse(), Ok(Codepage::Cp874), "{label}");
+        }
+        for label in [
+            "",
+            "cp",
+            "932",
+            "utf-8",
+            "cp437x",
+            "4370000000000000000000000000000000",
+        ] {
+            assert_eq!(
+                label.parse::<Codepage>(),
+                Err(UnknownEncoding(label.into())),
+                "{label}"
+            );
+        }
+    }
+}
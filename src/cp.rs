@@ -0,0 +1,695 @@
+use core::convert::TryFrom;
+use core::fmt;
+use core::num::NonZeroU8;
+
+use super::code_table::*;
+use super::code_table_type::TableType;
+use super::translit::best_fit_char;
+use super::OEMCPHashMap;
+use super::{
+    decode_char_complete_table, decode_char_incomplete_table_checked,
+    decode_char_incomplete_table_lossy, encode_char_checked, encode_char_lossy,
+};
+
+/// Single byte character in the code page `ID`
+///
+/// Conversion traits are implemented only for code pages supported by this crate, so unsupported IDs (e.g. `Cp<932>`) are rejected at compile time.
+/// Aliases (e.g. [`Cp437`] for `Cp<437>`) are also provided for each code page.
+///
+/// Write generic code against [`IncompleteCp`] (all code pages) or [`CompleteCp`] (code pages without undefined codepoints).
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use oem_cp::{Cp, Cp437, Cp874};
+///
+/// assert_eq!(char::from(Cp437::from(0xFB)), '√');
+/// assert_eq!(u8::from(Cp::<437>::try_from('√').unwrap()), 0xFB);
+/// assert_eq!(Cp437::from(0xAB).to_string(), "½");
+/// // means shrimp in Thai (U+E49 => 0xE9)
+/// assert_eq!(char::from(Cp874::try_from(0xE9).unwrap()), '\u{E49}');
+/// // 0xDB-0xDE,0xFC-0xFF is undefined in CP874 in Windows
+/// assert!(Cp874::try_from(0xDB).is_err());
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cp<const ID: u16>(u8);
+
+/// Error returned when a byte is undefined in the code page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryFromU8Error;
+
+/// Error returned when a character can't be encoded in the code page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryFromCharError;
+
+/// Error returned when bytes can't be converted to an array of [`Cp`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryFromBytesError {
+    /// The number of bytes differs from the length of the array
+    Length {
+        /// Length of the array
+        expected: usize,
+        /// Number of bytes
+        actual: usize,
+    },
+    /// The byte at `index` is undefined in the code page
+    Undefined {
+        /// Index of the first undefined byte
+        index: usize,
+    },
+}
+
+/// Kind of errors in conversion between [`Cp`] types (or strings) and bytes or chars
+///
+/// More variants may be added in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConversionErrorKind {
+    /// The byte is undefined in the code page, or the character isn't representable in it
+    OutOfRange,
+    /// The character is representable but is a control character rejected by the caller
+    Control,
+}
+
+impl ConversionErrorKind {
+    /// Returns a short description of the kind
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ConversionErrorKind::OutOfRange => "out of range",
+            ConversionErrorKind::Control => "control character",
+        }
+    }
+}
+
+impl fmt::Display for ConversionErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFromU8Error {
+    /// Returns the kind of the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use oem_cp::{ConversionErrorKind, Cp874};
+    ///
+    /// assert_eq!(Cp874::try_from(0xDB).unwrap_err().kind(), ConversionErrorKind::OutOfRange);
+    /// ```
+    pub const fn kind(&self) -> ConversionErrorKind {
+        ConversionErrorKind::OutOfRange
+    }
+}
+
+impl TryFromCharError {
+    /// Returns the kind of the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use oem_cp::{ConversionErrorKind, Cp437};
+    ///
+    /// assert_eq!(Cp437::try_from('日').unwrap_err().kind(), ConversionErrorKind::OutOfRange);
+    /// ```
+    pub const fn kind(&self) -> ConversionErrorKind {
+        ConversionErrorKind::OutOfRange
+    }
+}
+
+impl fmt::Display for TryFromU8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("byte out of range: undefined codepoint in the code page")
+    }
+}
+
+impl fmt::Display for TryFromCharError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("char out of range: not representable in the code page")
+    }
+}
+
+impl fmt::Display for TryFromBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryFromBytesError::Length { expected, actual } => {
+                write!(f, "expected {expected} bytes, got {actual}")
+            }
+            TryFromBytesError::Undefined { index } => {
+                write!(f, "byte out of range: undefined codepoint at index {index}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for TryFromU8Error {}
+impl core::error::Error for TryFromCharError {}
+impl core::error::Error for TryFromBytesError {}
+
+/// Character in a code page which may have undefined codepoints
+///
+/// All [`Cp`] types supported by this crate implement this trait.
+pub trait IncompleteCp:
+    Copy + Into<u8> + Into<char> + TryFrom<char, Error = TryFromCharError>
+{
+    /// Code page ID (e.g. `437` for CP437)
+    const ID: u16;
+
+    /// Returns the decoding table of the code page
+    fn decoding_table() -> &'static TableType;
+
+    /// Returns the encoding table of the code page
+    fn encoding_table() -> &'static OEMCPHashMap<char, u8>;
+
+    /// Converts a byte, returning `None` if it is undefined in the code page
+    fn from_u8_checked(byte: u8) -> Option<Self>;
+
+    /// Encodes a char to a byte without constructing `Self`, returning `None` if it's undefined in the code page
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{Cp437, IncompleteCp};
+    ///
+    /// assert_eq!(Cp437::encode_char('√'), Some(0xFB));
+    /// assert_eq!(Cp437::encode_char('日'), None);
+    /// ```
+    fn encode_char(c: char) -> Option<u8> {
+        encode_char_checked(c, Self::encoding_table())
+    }
+
+    /// Encodes a char to a byte without constructing `Self`
+    ///
+    /// Undefined codepoints are replaced with `0x3F` (`?`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{Cp437, IncompleteCp};
+    ///
+    /// assert_eq!(Cp437::encode_char_lossy('√'), 0xFB);
+    /// assert_eq!(Cp437::encode_char_lossy('日'), b'?');
+    /// ```
+    fn encode_char_lossy(c: char) -> u8 {
+        encode_char_lossy(c, Self::encoding_table())
+    }
+}
+
+/// Character in a code page which doesn't have any undefined codepoints
+///
+/// Any byte can be converted with `From<u8>`.
+pub trait CompleteCp: IncompleteCp + From<u8> {}
+
+impl<const ID: u16> From<Cp<ID>> for u8 {
+    fn from(cp: Cp<ID>) -> u8 {
+        cp.0
+    }
+}
+
+impl<const ID: u16> Cp<ID>
+where
+    Self: IncompleteCp,
+{
+    /// Decodes the byte to a char (the inverse of [`IncompleteCp::encode_char`])
+    ///
+    /// Same as `char::from(self)`.
+    /// It never fails even in incomplete code pages since undefined codepoints are rejected on construction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use oem_cp::{Cp437, Cp874, IncompleteCp};
+    ///
+    /// assert_eq!(Cp437::from(0xFB).decode(), '√');
+    /// assert_eq!(Cp874::try_from(0xE9).unwrap().decode(), '\u{E49}');
+    /// assert_eq!(Cp437::encode_char(Cp437::from(0xAB).decode()), Some(0xAB));
+    /// ```
+    pub fn decode(self) -> char {
+        self.into()
+    }
+
+    /// Returns the byte as [`NonZeroU8`], or `None` if it is NUL (0x00)
+    ///
+    /// Useful for collecting NUL-free bytes for C strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Cp437;
+    ///
+    /// assert_eq!(Cp437::from(0xFB).as_nonzero().map(u8::from), Some(0xFB));
+    /// assert_eq!(Cp437::from(0x00).as_nonzero(), None);
+    /// ```
+    pub fn as_nonzero(self) -> Option<NonZeroU8> {
+        NonZeroU8::new(self.0)
+    }
+
+    /// Converts an array of characters to bytes
+    ///
+    /// `From<[Cp<ID>; N]> for [u8; N]` can't be implemented because of the orphan rule, so use this instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Cp437;
+    ///
+    /// let field = [Cp437::from(0xFB), Cp437::from(b'2')];
+    /// assert_eq!(Cp437::array_to_bytes(field), [0xFB, b'2']);
+    /// ```
+    pub fn array_to_bytes<const N: usize>(array: [Self; N]) -> [u8; N] {
+        array.map(|cp| cp.0)
+    }
+
+    /// Converts a byte without checking that it's defined in the code page
+    ///
+    /// Use this for bytes validated in bulk beforehand (e.g. with [`TableType::decode_cow_checked`](crate::code_table_type::TableType::decode_cow_checked)) to skip the per-byte check of `TryFrom<u8>`.
+    ///
+    /// # Safety
+    ///
+    /// `byte` must be defined in the code page.
+    /// Otherwise the behavior is undefined, like [`str::from_utf8_unchecked`](core::str::from_utf8_unchecked) with invalid UTF-8.
+    /// This is checked only in debug builds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Cp874;
+    ///
+    /// // 0xE9 is defined in CP874
+    /// let cp = unsafe { Cp874::from_u8_unchecked(0xE9) };
+    /// assert_eq!(char::from(cp), '\u{E49}');
+    /// ```
+    pub unsafe fn from_u8_unchecked(byte: u8) -> Self {
+        debug_assert!(
+            Self::from_u8_checked(byte).is_some(),
+            "undefined codepoint 0x{byte:02X}"
+        );
+        Self(byte)
+    }
+
+    /// Converts a slice of bytes to an array of characters, validating every byte
+    ///
+    /// Fails if the length of `bytes` isn't `N` or any byte is undefined in the code page.
+    /// `TryFrom<&[u8]> for [Cp<ID>; N]` can't be implemented because of the orphan rule, so use this instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{Cp874, TryFromBytesError};
+    ///
+    /// let field: [Cp874; 2] = Cp874::array_try_from_bytes(&[0xA1, 0xE9]).unwrap();
+    /// assert_eq!(field.map(char::from), ['\u{E01}', '\u{E49}']);
+    /// assert_eq!(Cp874::array_try_from_bytes::<2>(&[0xA1, 0xDB]), Err(TryFromBytesError::Undefined { index: 1 }));
+    /// assert_eq!(Cp874::array_try_from_bytes::<2>(&[0xA1]), Err(TryFromBytesError::Length { expected: 2, actual: 1 }));
+    /// ```
+    pub fn array_try_from_bytes<const N: usize>(
+        bytes: &[u8],
+    ) -> Result<[Self; N], TryFromBytesError> {
+        let bytes: &[u8; N] = bytes.try_into().map_err(|_| TryFromBytesError::Length {
+            expected: N,
+            actual: bytes.len(),
+        })?;
+        if let Some(index) = bytes
+            .iter()
+            .position(|byte| Self::from_u8_checked(*byte).is_none())
+        {
+            return Err(TryFromBytesError::Undefined { index });
+        }
+        Ok(bytes.map(Self))
+    }
+
+    /// Converts a char, falling back to a similar char if it's undefined in the code page
+    ///
+    /// Unlike `TryFrom<char>`, which is strict, this falls back with a small built-in heuristic (not Windows' best-fit tables): fullwidth ASCII is mapped to ASCII and accented Latin letters to their base letters.
+    /// Returns `None` if neither `c` nor its fallback can be encoded, including chars without a single-char fallback such as `€`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{Cp437, Cp866, Cp874};
+    ///
+    /// assert_eq!(Cp437::from_char_best_fit('é').map(u8::from), Some(0x82));
+    /// assert_eq!(Cp866::from_char_best_fit('é').map(u8::from), Some(b'e'));
+    /// assert_eq!(Cp437::from_char_best_fit('Ａ').map(u8::from), Some(b'A'));
+    /// assert_eq!(Cp437::from_char_best_fit('日'), None);
+    /// // '€' is transliterated to "EUR", which isn't a single char
+    /// assert_eq!(Cp437::from_char_best_fit('€'), None);
+    /// // Also available for code pages with undefined codepoints
+    /// assert_eq!(Cp874::from_char_best_fit('\u{E01}').map(u8::from), Some(0xA1));
+    /// assert_eq!(Cp874::from_char_best_fit('é').map(u8::from), Some(b'e'));
+    /// ```
+    pub fn from_char_best_fit(c: char) -> Option<Self> {
+        let byte = Self::encode_char(c).or_else(|| Self::encode_char(best_fit_char(c)?))?;
+        Self::from_u8_checked(byte)
+    }
+}
+
+impl<const ID: u16> Cp<ID>
+where
+    Self: CompleteCp,
+{
+    /// Converts bytes to an array of characters (the inverse of [`Cp::array_to_bytes`])
+    ///
+    /// Available only for code pages without undefined codepoints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::Cp437;
+    ///
+    /// let field = Cp437::array_from_bytes([0xFB, b'2']);
+    /// assert_eq!(field.map(char::from), ['√', '2']);
+    /// ```
+    pub fn array_from_bytes<const N: usize>(bytes: [u8; N]) -> [Self; N] {
+        bytes.map(Self)
+    }
+}
+
+impl<const ID: u16> fmt::Display for Cp<ID>
+where
+    Self: IncompleteCp,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c: char = (*self).into();
+        fmt::Display::fmt(&c, f)
+    }
+}
+
+/// Returns the length in bytes of `cps` decoded to UTF-8
+///
+/// Use it to size the buffer for [`decode_to_str`].
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{decoded_utf8_len, Cp437};
+///
+/// // "√A" (U+221A takes 3 bytes)
+/// assert_eq!(decoded_utf8_len(&[Cp437::from(0xFB), Cp437::from(b'A')]), 4);
+/// ```
+pub fn decoded_utf8_len<T: IncompleteCp>(cps: &[T]) -> usize {
+    cps.iter()
+        .map(|&cp| Into::<char>::into(cp).len_utf8())
+        .sum()
+}
+
+/// Decodes typed single byte characters into `scratch` and returns the decoded string borrowing it
+///
+/// Nothing is allocated, so it's available without the `alloc` feature.
+/// Returns `Err` with the needed length ([`decoded_utf8_len`]) if `scratch` is too small; `scratch` may be partially overwritten then.
+///
+/// # Arguments
+///
+/// * `cps` - typed single byte characters
+/// * `scratch` - buffer to write the UTF-8 string into
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{decode_to_str, Cp437};
+///
+/// let cps = [0xFB, 0xAC, 0x3D, 0xAB].map(Cp437::from);
+/// let mut scratch = [0; 16];
+/// assert_eq!(decode_to_str(&cps, &mut scratch), Ok("√¼=½"));
+/// let mut scratch = [0; 4];
+/// assert_eq!(decode_to_str(&cps, &mut scratch), Err(8));
+/// ```
+pub fn decode_to_str<'b, T: IncompleteCp>(
+    cps: &[T],
+    scratch: &'b mut [u8],
+) -> Result<&'b str, usize> {
+    let mut len = 0;
+    for &cp in cps {
+        let c: char = cp.into();
+        let end = len + c.len_utf8();
+        let Some(dst) = scratch.get_mut(len..end) else {
+            return Err(decoded_utf8_len(cps));
+        };
+        c.encode_utf8(dst);
+        len = end;
+    }
+    // Only whole chars have been written
+    Ok(core::str::from_utf8(&scratch[..len]).unwrap())
+}
+
+/// Implements conversion traits for `Cp<$id>`
+///
+/// Invoked for each code page in the generated `cp_impl.rs`.
+macro_rules! cp_impl {
+    (Complete, $id:literal, $decoding:ident, $encoding:ident, $table:ident) => {
+        impl From<u8> for Cp<$id> {
+            fn from(byte: u8) -> Self {
+                Self(byte)
+            }
+        }
+
+        impl From<Cp<$id>> for char {
+            fn from(cp: Cp<$id>) -> char {
+                decode_char_complete_table(cp.0, &$decoding)
+            }
+        }
+
+        impl IncompleteCp for Cp<$id> {
+            const ID: u16 = $id;
+
+            fn decoding_table() -> &'static TableType {
+                &$table
+            }
+
+            fn encoding_table() -> &'static OEMCPHashMap<char, u8> {
+                &$encoding
+            }
+
+            fn from_u8_checked(byte: u8) -> Option<Self> {
+                Some(Self(byte))
+            }
+        }
+
+        impl CompleteCp for Cp<$id> {}
+
+        cp_impl!(@common $id, $encoding);
+    };
+    (Incomplete, $id:literal, $decoding:ident, $encoding:ident, $table:ident) => {
+        impl TryFrom<u8> for Cp<$id> {
+            type Error = TryFromU8Error;
+
+            fn try_from(byte: u8) -> Result<Self, Self::Error> {
+                decode_char_incomplete_table_checked(byte, &$decoding)
+                    .map(|_| Self(byte))
+                    .ok_or(TryFromU8Error)
+            }
+        }
+
+        impl From<Cp<$id>> for char {
+            fn from(cp: Cp<$id>) -> char {
+                // Never replaced since `TryFrom<u8>` has rejected undefined codepoints
+                decode_char_incomplete_table_lossy(cp.0, &$decoding)
+            }
+        }
+
+        impl IncompleteCp for Cp<$id> {
+            const ID: u16 = $id;
+
+            fn decoding_table() -> &'static TableType {
+                &$table
+            }
+
+            fn encoding_table() -> &'static OEMCPHashMap<char, u8> {
+                &$encoding
+            }
+
+            fn from_u8_checked(byte: u8) -> Option<Self> {
+                Self::try_from(byte).ok()
+            }
+        }
+
+        cp_impl!(@common $id, $encoding);
+    };
+    (@common $id:literal, $encoding:ident) => {
+        impl TryFrom<char> for Cp<$id> {
+            type Error = TryFromCharError;
+
+            fn try_from(c: char) -> Result<Self, Self::Error> {
+                encode_char_checked(c, &$encoding)
+                    .map(Self)
+                    .ok_or(TryFromCharError)
+            }
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/cp_impl.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip<T: IncompleteCp>(byte: u8) -> Option<u8> {
+        let c: char = T::from_u8_checked(byte)?.into();
+        T::try_from(c).ok().map(Into::into)
+    }
+
+    #[test]
+    fn cp_roundtrip_test() {
+        for byte in 0..=255 {
+            assert_eq!(roundtrip::<Cp437>(byte), Some(byte));
+            assert_eq!(
+                char::from(Cp437::from(byte)).to_string(),
+                Cp437::from(byte).to_string()
+            );
+        }
+        for byte in [0xDB, 0xDC, 0xDD, 0xDE, 0xFC, 0xFD, 0xFE, 0xFF] {
+            assert_eq!(Cp874::try_from(byte), Err(TryFromU8Error));
+            assert_eq!(roundtrip::<Cp874>(byte), None);
+        }
+        assert_eq!(roundtrip::<Cp874>(0xE9), Some(0xE9));
+    }
+
+    #[test]
+    fn cp_try_from_char_test() {
+        assert_eq!(Cp437::try_from('π').map(u8::from), Ok(0xE3));
+        assert_eq!(Cp::<737>::try_from('Α').map(u8::from), Ok(0x80));
+        assert_eq!(Cp437::try_from('日'), Err(TryFromCharError));
+        assert_eq!(<Cp874 as IncompleteCp>::ID, 874);
+    }
+
+    #[test]
+    fn from_char_best_fit_test() {
+        for c in ['π', 'A', 'é', '日'] {
+            assert_eq!(Cp437::from_char_best_fit(c), Cp437::try_from(c).ok());
+        }
+        assert_eq!(Cp437::try_from('Ā'), Err(TryFromCharError));
+        assert_eq!(Cp437::from_char_best_fit('Ā').map(u8::from), Some(b'A'));
+        assert_eq!(Cp437::from_char_best_fit('–').map(u8::from), Some(b'-'));
+        assert_eq!(
+            Cp437::from_char_best_fit('\u{3000}').map(u8::from),
+            Some(b' ')
+        );
+        assert_eq!(Cp437::from_char_best_fit('€'), None);
+        assert_eq!(Cp866::from_char_best_fit('ｚ').map(u8::from), Some(b'z'));
+        for c in ['\u{E01}', '\u{E49}', 'A', '日'] {
+            assert_eq!(Cp874::from_char_best_fit(c), Cp874::try_from(c).ok());
+        }
+        assert_eq!(Cp874::from_char_best_fit('ü').map(u8::from), Some(b'u'));
+    }
+
+    #[test]
+    fn as_nonzero_test() {
+        assert_eq!(Cp437::from(0).as_nonzero(), None);
+        for byte in 1..=255 {
+            assert_eq!(
+                Cp437::from(byte).as_nonzero().map(NonZeroU8::get),
+                Some(byte)
+            );
+        }
+        assert_eq!(Cp874::try_from(0).unwrap().as_nonzero(), None);
+    }
+
+    #[test]
+    fn array_bytes_test() {
+        let bytes: [u8; 256] = core::array::from_fn(|i| i as u8);
+        let cps = Cp437::array_from_bytes(bytes);
+        assert_eq!(cps.map(u8::from), bytes);
+        assert_eq!(Cp437::array_to_bytes(cps), bytes);
+        let cp874 = [
+            Cp874::try_from(0xE9).unwrap(),
+            Cp874::try_from(0xA1).unwrap(),
+        ];
+        assert_eq!(Cp874::array_to_bytes(cp874), [0xE9, 0xA1]);
+        assert_eq!(Cp437::array_to_bytes([]), [0u8; 0]);
+    }
+
+    #[test]
+    fn array_try_from_bytes_test() {
+        // ก-ฐ
+        let bytes: [u8; 16] = core::array::from_fn(|i| 0xA1 + i as u8);
+        let cps: [Cp874; 16] = Cp874::array_try_from_bytes(&bytes).unwrap();
+        assert_eq!(Cp874::array_to_bytes(cps), bytes);
+        for byte in [0xDB, 0xFF] {
+            let mut field = [0xA1; 16];
+            field[7] = byte;
+            assert_eq!(
+                Cp874::array_try_from_bytes::<16>(&field),
+                Err(TryFromBytesError::Undefined { index: 7 })
+            );
+        }
+        assert_eq!(
+            Cp437::array_try_from_bytes::<2>(&[0xFB; 3]),
+            Err(TryFromBytesError::Length {
+                expected: 2,
+                actual: 3
+            })
+        );
+        assert_eq!(Cp437::array_try_from_bytes::<0>(&[]), Ok([]));
+        assert_eq!(
+            TryFromBytesError::Undefined { index: 7 }.to_string(),
+            "byte out of range: undefined codepoint at index 7"
+        );
+    }
+
+    #[test]
+    fn from_u8_unchecked_test() {
+        for byte in (0..=255).filter(|byte| Cp874::try_from(*byte).is_ok()) {
+            let cp = unsafe { Cp874::from_u8_unchecked(byte) };
+            assert_eq!(Ok(cp), Cp874::try_from(byte));
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "undefined codepoint 0xDB")]
+    fn from_u8_unchecked_debug_test() {
+        // 0xDB is undefined in CP874; caught by `debug_assert!` before the value is constructed
+        let _ = unsafe { Cp874::from_u8_unchecked(0xDB) };
+    }
+
+    #[test]
+    fn decode_to_str_test() {
+        let cps = (0..=255)
+            .filter_map(Cp874::from_u8_checked)
+            .collect::<Vec<_>>();
+        let expected = cps.iter().map(|&cp| char::from(cp)).collect::<String>();
+        assert_eq!(decoded_utf8_len(&cps), expected.len());
+        let mut scratch = vec![0; expected.len()];
+        assert_eq!(decode_to_str(&cps, &mut scratch), Ok(expected.as_str()));
+        let mut scratch = vec![0; expected.len() - 1];
+        assert_eq!(decode_to_str(&cps, &mut scratch), Err(expected.len()));
+        assert_eq!(decode_to_str::<Cp437>(&[], &mut []), Ok(""));
+    }
+
+    #[test]
+    fn encode_char_test() {
+        for c in ['π', '√', 'A', '日', '€'] {
+            assert_eq!(Cp437::encode_char(c), Cp437::try_from(c).ok().map(u8::from));
+            assert_eq!(
+                Cp437::encode_char_lossy(c),
+                Cp437::encode_char(c).unwrap_or(b'?')
+            );
+        }
+    }
+
+    #[test]
+    fn decode_test() {
+        for byte in 0..=255 {
+            assert_eq!(Cp437::from(byte).decode(), char::from(Cp437::from(byte)));
+            if let Ok(cp) = Cp874::try_from(byte) {
+                assert_eq!(Cp874::encode_char(cp.decode()), Some(byte));
+            }
+        }
+    }
+
+    #[test]
+    fn error_test() {
+        let u8_error = Cp874::try_from(0xDB).unwrap_err();
+        let char_error = Cp874::try_from('日').unwrap_err();
+        assert_eq!(u8_error.kind(), ConversionErrorKind::OutOfRange);
+        assert_eq!(char_error.kind(), ConversionErrorKind::OutOfRange);
+        assert_ne!(u8_error.to_string(), char_error.to_string());
+        assert_eq!(ConversionErrorKind::OutOfRange.to_string(), "out of range");
+    }
+}
@@ -0,0 +1,421 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use super::code_table::DECODING_TABLE_CP_MAP;
+use super::code_table_type::TableType;
+use super::strategy::{AsciiFast, DecodeStrategy};
+
+/// Scores how likely `src` is encoded with `table`
+///
+/// Returns `None` if `src` contains undefined codepoints.
+/// Otherwise, each non-ASCII byte adds 1 if it's decoded to an alphabetic character and subtracts 1 if not.
+pub(crate) fn score(src: &[u8], table: &TableType) -> Option<isize> {
+    let mut score = 0;
+    for &byte in src
+        .iter()
+        .filter(|byte| AsciiFast::decode_direct(**byte).is_err())
+    {
+        if table.decode_char_checked(byte)?.is_alphabetic() {
+            score += 1;
+        } else {
+            score -= 1;
+        }
+    }
+    Some(score)
+}
+
+/// Count how many times each byte value appears
+///
+/// `histogram[b]` is the number of `b` in `src`.
+/// The distribution of 0x80-0xFF gives a hint of the code page (e.g. many hits in 0xB0-0xDF suggest box-drawing characters of DOS code pages).
+///
+/// # Arguments
+///
+/// * `src` - bytes encoded in SBCS
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::byte_histogram;
+///
+/// let histogram = byte_histogram(&[0xFB, b'a', 0xFB]);
+/// assert_eq!(histogram[0xFB], 2);
+/// assert_eq!(histogram[b'a' as usize], 1);
+/// assert_eq!(histogram.iter().sum::<u32>(), 3);
+/// ```
+pub fn byte_histogram(src: &[u8]) -> [u32; 256] {
+    let mut histogram = [0; 256];
+    for &byte in src {
+        histogram[byte as usize] += 1;
+    }
+    histogram
+}
+
+/// Coarse classification of the script of text
+///
+/// More variants may be added in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Script {
+    /// Latin alphabet (including accented letters)
+    Latin,
+    /// Cyrillic alphabet
+    Cyrillic,
+    /// Greek alphabet
+    Greek,
+    /// Arabic script (including presentation forms)
+    Arabic,
+    /// Hebrew alphabet
+    Hebrew,
+    /// Thai script
+    Thai,
+    /// Other scripts, or no letters at all
+    Other,
+}
+
+impl Script {
+    const COUNTED: [Script; 6] = [
+        Script::Latin,
+        Script::Cyrillic,
+        Script::Greek,
+        Script::Arabic,
+        Script::Hebrew,
+        Script::Thai,
+    ];
+
+    /// Classifies a letter by its Unicode block
+    fn of(c: char) -> Script {
+        match c {
+            'A'..='Z' | 'a'..='z' | '\u{C0}'..='\u{24F}' | '\u{1E00}'..='\u{1EFF}' => Script::Latin,
+            '\u{400}'..='\u{4FF}' => Script::Cyrillic,
+            '\u{370}'..='\u{3FF}' | '\u{1F00}'..='\u{1FFF}' => Script::Greek,
+            '\u{600}'..='\u{6FF}' | '\u{FB50}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}' => {
+                Script::Arabic
+            }
+            '\u{590}'..='\u{5FF}' => Script::Hebrew,
+            '\u{E00}'..='\u{E7F}' => Script::Thai,
+            _ => Script::Other,
+        }
+    }
+}
+
+impl TableType {
+    /// Returns the script most letters in `src` are written in
+    ///
+    /// Only alphabetic characters are counted; digits, symbols, box-drawing characters and undefined codepoints are ignored.
+    /// Ties are broken in the order of the variants of [`Script`].
+    /// Returns [`Script::Other`] if `src` has no letters of the known scripts.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - bytes encoded in SBCS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::{DECODING_TABLE_CP437, DECODING_TABLE_CP866};
+    /// use oem_cp::code_table_type::TableType::Complete;
+    /// use oem_cp::Script;
+    ///
+    /// // "Привет, world" in CP866
+    /// let src = [0x8F, 0xE0, 0xA8, 0xA2, 0xA5, 0xE2, b',', b' ', b'w', b'o', b'r', b'l', b'd'];
+    /// assert_eq!(Complete(&DECODING_TABLE_CP866).dominant_script(&src), Script::Cyrillic);
+    /// assert_eq!(Complete(&DECODING_TABLE_CP437).dominant_script(b"12 + 34"), Script::Other);
+    /// ```
+    pub fn dominant_script(&self, src: &[u8]) -> Script {
+        let mut counts = [0usize; Script::COUNTED.len()];
+        for &byte in src {
+            let Some(c) = self.decode_char_checked(byte) else {
+                continue;
+            };
+            if !c.is_alphabetic() {
+                continue;
+            }
+            let script = Script::of(c);
+            if let Some(i) = Script::COUNTED.iter().position(|s| *s == script) {
+                counts[i] += 1;
+            }
+        }
+        let mut best = (Script::Other, 0);
+        for (script, count) in Script::COUNTED.into_iter().zip(counts) {
+            if count > best.1 {
+                best = (script, count);
+            }
+        }
+        best.0
+    }
+}
+
+/// Guess the code page of bytes from candidates
+///
+/// Candidates that bytes have undefined codepoints in or that aren't supported by this crate are ignored.
+/// The candidate where the most non-ASCII bytes are decoded to letters (and the fewest to symbols) is chosen; ties are broken by the order of `candidates`.
+/// Returns `None` if no candidates remain.
+///
+/// This is just a heuristic. Short or symbol-heavy samples may be misdetected.
+///
+/// # Arguments
+///
+/// * `src` - bytes encoded in an unknown SBCS
+/// * `candidates` - code page IDs to try
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::detect;
+///
+/// // "Привет" in CP866
+/// assert_eq!(detect(&[0x8F, 0xE0, 0xA8, 0xA2, 0xA5, 0xE2], &[437, 866]), Some(866));
+/// // "Ísland" in CP850
+/// assert_eq!(detect(&[0xD6, 0x73, 0x6C, 0x61, 0x6E, 0x64], &[437, 850]), Some(850));
+/// // 0xDB is undefined in CP874
+/// assert_eq!(detect(&[0xDB], &[874]), None);
+/// ```
+pub fn detect(src: &[u8], candidates: &[u16]) -> Option<u16> {
+    let mut best: Option<(u16, isize)> = None;
+    for &code_page in candidates {
+        let Some(table) = DECODING_TABLE_CP_MAP.get(&code_page) else {
+            continue;
+        };
+        let Some(score) = score(src, table) else {
+            continue;
+        };
+        if best.map_or(true, |(_, best_score)| score > best_score) {
+            best = Some((code_page, score));
+        }
+    }
+    best.map(|(code_page, _)| code_page)
+}
+
+/// Result of [`probe`] for a code page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeResult {
+    /// Code page ID
+    pub page: u16,
+    /// Whether the bytes have no undefined codepoints in the code page (i.e. checked decoding succeeds)
+    pub checked_ok: bool,
+    /// Number of U+FFFD emitted by lossy decoding (i.e. number of undefined bytes)
+    pub replacements: usize,
+    /// Ratio of bytes decoded to printable (non-control) characters, from `0.0` to `1.0`
+    ///
+    /// Undefined bytes count as non-printable. It's `1.0` for empty bytes.
+    pub printable_ratio: f64,
+}
+
+/// Report how well bytes fit each candidate code page
+///
+/// This gives more details than [`detect`] for e.g. showing a ranked table of candidates.
+/// Candidates that aren't supported by this crate are ignored, and the results are in the order of `pages`.
+///
+/// # Arguments
+///
+/// * `bytes` - bytes encoded in an unknown SBCS
+/// * `pages` - code page IDs to try
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::probe;
+///
+/// let results = probe(&[0x41, 0xDB, 0x0A], &[437, 874, 932]);
+/// assert_eq!(results.len(), 2);
+/// assert_eq!((results[0].page, results[0].checked_ok, results[0].replacements), (437, true, 0));
+/// assert_eq!((results[1].page, results[1].checked_ok, results[1].replacements), (874, false, 1));
+/// assert_eq!(results[1].printable_ratio, 1.0 / 3.0);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn probe(bytes: &[u8], pages: &[u16]) -> Vec<ProbeResult> {
+    pages
+        .iter()
+        .filter_map(|&page| {
+            let table = DECODING_TABLE_CP_MAP.get(&page)?;
+            let mut replacements = 0;
+            let mut printable = 0;
+            for &byte in bytes {
+                match table.decode_char_checked(byte) {
+                    Some(c) if !c.is_control() => printable += 1,
+                    Some(_) => {}
+                    None => replacements += 1,
+                }
+            }
+            Some(ProbeResult {
+                page,
+                checked_ok: score(bytes, table).is_some(),
+                replacements,
+                printable_ratio: if bytes.is_empty() {
+                    1.0
+                } else {
+                    printable as f64 / bytes.len() as f64
+                },
+            })
+        })
+        .collect()
+}
+
+/// Guess which of the DOS Greek code pages (CP737 and CP869) bytes are encoded with
+///
+/// CP737 (formerly 437G) was common in MS-DOS in Greece and keeps the box-drawing characters of CP437, whereas CP869 (IBM Modern Greek) trades some of them for accented capitals and symbols.
+/// Files from MS-DOS/Windows are likely to be CP737, so prefer it unless the data says otherwise.
+///
+/// This is [`detect`] restricted to `[737, 869]`, so CP737 is chosen on ties.
+///
+/// # Arguments
+///
+/// * `sample` - bytes encoded in CP737 or CP869
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::greek_page_for;
+///
+/// // Archimedes in Greek
+/// assert_eq!(greek_page_for(&[0x80, 0xA8, 0xAE, 0xA0, 0xA3, 0xE3, 0x9B, 0x9E, 0xAA]), Some(737));
+/// assert_eq!(greek_page_for(&[0xA4, 0xEB, 0xF4, 0xE3, 0xE6, 0x9E, 0xDD, 0xE1, 0xED]), Some(869));
+/// ```
+pub fn greek_page_for(sample: &[u8]) -> Option<u16> {
+    detect(sample, &[737, 869])
+}
+
+/// Guess the code page of the beginning of a reader from candidates
+///
+/// Reads up to `sample` bytes from `reader` and runs [`detect`] over them.
+/// The read bytes are returned together, so prepend them (e.g. with [`std::io::Read::chain`]) to decode the whole stream.
+///
+/// # Arguments
+///
+/// * `reader` - reader of bytes encoded in an unknown SBCS
+/// * `candidates` - code page IDs to try
+/// * `sample` - maximum number of bytes to read
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Cursor, Read};
+/// use oem_cp::detect_reader;
+///
+/// // "Привет мир" in CP866
+/// let mut reader = Cursor::new(vec![0x8F, 0xE0, 0xA8, 0xA2, 0xA5, 0xE2, 0x20, 0xAC, 0xA8, 0xE0]);
+/// let (code_page, sampled) = detect_reader(&mut reader, &[437, 866], 6).unwrap();
+/// assert_eq!(code_page, Some(866));
+///
+/// let mut whole = Vec::new();
+/// sampled.as_slice().chain(reader).read_to_end(&mut whole).unwrap();
+/// assert_eq!(whole.len(), 10);
+/// ```
+#[cfg(feature = "std")]
+pub fn detect_reader<R: std::io::Read>(
+    reader: &mut R,
+    candidates: &[u16],
+    sample: usize,
+) -> std::io::Result<(Option<u16>, std::vec::Vec<u8>)> {
+    use std::io::Read;
+
+    let mut sampled = std::vec::Vec::with_capacity(sample);
+    reader.take(sample as u64).read_to_end(&mut sampled)?;
+    Ok((detect(&sampled, candidates), sampled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_test() {
+        // "Привет" in CP866
+        let privet = [0x8F, 0xE0, 0xA8, 0xA2, 0xA5, 0xE2];
+        assert_eq!(detect(&privet, &[866, 437]), Some(866));
+        // Unsupported code pages are ignored
+        assert_eq!(detect(&privet, &[932, 866]), Some(866));
+        assert_eq!(detect(&privet, &[932]), None);
+        assert_eq!(detect(&privet, &[]), None);
+        // Ties are broken by the order of candidates
+        assert_eq!(detect(b"ASCII", &[850, 437]), Some(850));
+        assert_eq!(detect(b"ASCII", &[437, 850]), Some(437));
+    }
+
+    #[test]
+    fn dominant_script_test() {
+        let table = |code_page| DECODING_TABLE_CP_MAP.get(&code_page).unwrap();
+        assert_eq!(table(437).dominant_script(b"hello"), Script::Latin);
+        assert_eq!(table(437).dominant_script(&[]), Script::Other);
+        assert_eq!(
+            table(437).dominant_script(&[0xB0, 0xC4, b'1']),
+            Script::Other
+        );
+        // "Καλη" in CP737 with 3 Latin letters
+        assert_eq!(
+            table(737).dominant_script(&[0x89, 0x98, 0xA2, 0x9E, b'a', b'b', b'c']),
+            Script::Greek
+        );
+        // Ties are broken in the order of the variants
+        assert_eq!(table(866).dominant_script(&[0x8F, b'a']), Script::Latin);
+        assert_eq!(table(862).dominant_script(&[0x80, 0x81]), Script::Hebrew);
+        assert_eq!(table(864).dominant_script(&[0xC8, 0xC9]), Script::Arabic);
+        assert_eq!(
+            table(874).dominant_script(&[0xA1, 0xD8, 0xE9, 0xA7, 0xDB]),
+            Script::Thai
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn probe_test() {
+        let src = [0xA1, 0xA2, 0xDB, 0xDC, 0x07, b'a'];
+        let results = probe(&src, &[932, 866, 874]);
+        assert_eq!(
+            results,
+            [
+                ProbeResult {
+                    page: 866,
+                    checked_ok: true,
+                    replacements: 0,
+                    printable_ratio: 5.0 / 6.0,
+                },
+                ProbeResult {
+                    page: 874,
+                    checked_ok: false,
+                    replacements: 2,
+                    printable_ratio: 3.0 / 6.0,
+                },
+            ]
+        );
+        assert_eq!(probe(&[], &[437])[0].printable_ratio, 1.0);
+        assert!(probe(&src, &[]).is_empty());
+    }
+
+    #[test]
+    fn byte_histogram_test() {
+        assert_eq!(byte_histogram(&[]), [0; 256]);
+        let src = (0..=255u8).chain(0x80..=0xFF).collect::<Vec<u8>>();
+        let histogram = byte_histogram(&src);
+        for byte in 0..=255u8 {
+            assert_eq!(histogram[byte as usize], if byte < 0x80 { 1 } else { 2 });
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn greek_page_for_test() {
+        use crate::code_table::{ENCODING_TABLE_CP737, ENCODING_TABLE_CP869};
+        use crate::encode_string_checked;
+
+        // cspell: disable
+        for word in ["Καλημέρα", "Αρχιμήδης", "ΕΛΛΑΔΑ"] {
+            let cp737 = encode_string_checked(word, &ENCODING_TABLE_CP737).unwrap();
+            let cp869 = encode_string_checked(word, &ENCODING_TABLE_CP869).unwrap();
+            assert_eq!(greek_page_for(&cp737), Some(737), "{word}");
+            assert_eq!(greek_page_for(&cp869), Some(869), "{word}");
+        }
+        // cspell: enable
+        assert_eq!(greek_page_for(b"ASCII"), Some(737));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn detect_reader_test() {
+        let mut reader = std::io::Cursor::new(b"short".to_vec());
+        let (code_page, sampled) = detect_reader(&mut reader, &[437], 100).unwrap();
+        assert_eq!(code_page, Some(437));
+        assert_eq!(sampled, b"short");
+    }
+}
@@ -0,0 +1,71 @@
+use super::strategy::{AsciiFast, DecodeStrategy, EncodeStrategy};
+use super::IncompleteCp;
+
+/// Extension methods for iterators over bytes encoded in SBCSs
+///
+/// Implemented for all iterators yielding `u8`.
+pub trait CpIteratorExt: Iterator<Item = u8> + Sized {
+    /// Re-encodes bytes in the code page `S` into the code page `T` lazily
+    ///
+    /// Each byte is decoded in `S` and then encoded in `T`; ASCII bytes are passed through without looking up tables.
+    /// Yields `None` for bytes undefined in `S` or whose chars aren't representable in `T`, so the caller can frame the result:
+    ///
+    /// * lossy: `.map(|byte| byte.unwrap_or(b'?'))`
+    /// * checked: `.collect::<Option<Vec<u8>>>()`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{Cp850, Cp852, CpIteratorExt};
+    ///
+    /// // "Öl à 5" in CP850 => CP852 ("à" isn't representable in CP852)
+    /// let src = [0x99, b'l', b' ', 0x85, b' ', b'5'];
+    /// let lossy = src.into_iter().transcode_cp::<Cp850, Cp852>().map(|byte| byte.unwrap_or(b'?')).collect::<Vec<u8>>();
+    /// assert_eq!(lossy, [0x99, b'l', b' ', b'?', b' ', b'5']);
+    /// let checked = src.into_iter().transcode_cp::<Cp850, Cp852>().collect::<Option<Vec<u8>>>();
+    /// assert_eq!(checked, None);
+    /// ```
+    fn transcode_cp<S: IncompleteCp, T: IncompleteCp>(
+        self,
+    ) -> core::iter::Map<Self, fn(u8) -> Option<u8>> {
+        self.map(transcode_byte::<S, T>)
+    }
+}
+
+impl<I: Iterator<Item = u8>> CpIteratorExt for I {}
+
+/// Re-encodes single byte in the code page `S` into the code page `T`
+fn transcode_byte<S: IncompleteCp, T: IncompleteCp>(byte: u8) -> Option<u8> {
+    match AsciiFast::decode_direct(byte) {
+        Ok(c) => AsciiFast::encode_direct(c),
+        Err(_) => T::encode_char(S::from_u8_checked(byte)?.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cp437, Cp850, Cp852, Cp874};
+
+    fn transcode_reference<S: IncompleteCp, T: IncompleteCp>(byte: u8) -> Option<u8> {
+        let c: char = S::from_u8_checked(byte)?.into();
+        T::try_from(c).ok().map(Into::into)
+    }
+
+    #[test]
+    fn transcode_cp_test() {
+        let transcoded = (0..=255).transcode_cp::<Cp850, Cp852>().collect::<Vec<_>>();
+        for byte in 0..=255 {
+            assert_eq!(
+                transcoded[byte as usize],
+                transcode_reference::<Cp850, Cp852>(byte)
+            );
+        }
+        assert!((0..=255)
+            .transcode_cp::<Cp437, Cp437>()
+            .eq((0..=255).map(Some)));
+        assert!((0..=255)
+            .transcode_cp::<Cp874, Cp874>()
+            .eq((0..=255).map(|byte| Cp874::from_u8_checked(byte).map(u8::from))));
+    }
+}
@@ -1,15 +1,42 @@
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
 include!(concat!(env!("OUT_DIR"), "/code_table.rs"));
 
+mod case;
+mod character;
+mod codepage;
+mod cp;
+mod detect;
+mod iter;
+#[cfg(feature = "alloc")]
+mod runtime;
+mod strategy;
 #[cfg(feature = "alloc")]
 mod string;
+#[cfg(feature = "test-util")]
+mod test_util;
+mod translit;
+#[cfg(all(windows, feature = "windows-compat"))]
+mod windows_compat;
 
+pub use case::*;
+pub use character::*;
+pub use codepage::*;
+pub use cp::*;
+pub use detect::*;
+pub use iter::*;
+#[cfg(feature = "alloc")]
+pub use runtime::*;
 #[cfg(feature = "alloc")]
 pub use string::*;
+#[cfg(feature = "test-util")]
+pub use test_util::*;
+pub use translit::*;
+#[cfg(all(windows, feature = "windows-compat"))]
+pub use windows_compat::*;
 
 /// The type of hashmap used in this crate.
 ///
@@ -18,9 +45,14 @@ pub use string::*;
 pub type OEMCPHashMap<K, V> = phf::Map<K, V>;
 
 pub mod code_table_type {
+    use core::hash::{Hash, Hasher};
+
     /// Wrapper enumerate for decoding tables
     ///
     /// It has 2 types: `Complete`, complete tables (it doesn't have undefined codepoints) / `Incomplete`, incomplete tables (does have ones)
+    ///
+    /// Equality and hashing are by identity: two values are equal if they wrap the same table (pointer), not tables with the same contents.
+    /// It's stable for the `'static` tables in [`code_table`](crate::code_table), so `TableType` can be used as a key to deduplicate code pages sharing a table.
     #[derive(Debug, Clone)]
     pub enum TableType {
         /// complete table, which doesn't have any undefined codepoints
@@ -28,4 +60,98 @@ pub mod code_table_type {
         /// incomplete table, which has some undefined codepoints
         Incomplete(&'static [Option<char>; 128]),
     }
+
+    impl TableType {
+        /// Creates a complete table from a decoding table for 0x80-0xFF
+        ///
+        /// Prefer this to `TableType::Complete` because the representation of the variants may change in the future.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use oem_cp::code_table::DECODING_TABLE_CP437;
+        /// use oem_cp::code_table_type::TableType;
+        ///
+        /// let table = TableType::complete(&DECODING_TABLE_CP437);
+        /// assert_eq!(table.decode_char_checked(0xFB), Some('√'));
+        /// ```
+        pub const fn complete(table: &'static [char; 128]) -> Self {
+            TableType::Complete(table)
+        }
+
+        /// Creates an incomplete table from a decoding table for 0x80-0xFF
+        ///
+        /// `None` in `table` means an undefined codepoint.
+        /// Prefer this to `TableType::Incomplete` because the representation of the variants may change in the future.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use oem_cp::code_table::DECODING_TABLE_CP874;
+        /// use oem_cp::code_table_type::TableType;
+        ///
+        /// let table = TableType::incomplete(&DECODING_TABLE_CP874);
+        /// assert_eq!(table.decode_char_checked(0xDB), None);
+        /// ```
+        pub const fn incomplete(table: &'static [Option<char>; 128]) -> Self {
+            TableType::Incomplete(table)
+        }
+
+        /// Checks if bytes 0x00-0x7F are decoded to the same codepoints (U+0000-U+007F)
+        ///
+        /// When it returns `true`, ASCII bytes and characters can be passed through without looking up tables.
+        /// Both variants describe only 0x80-0xFF and keep ASCII as is, so it's always `true` for now; it will return `false` for tables remapping the lower half if they are added.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use oem_cp::code_table::CP437_TABLE;
+        ///
+        /// assert!(CP437_TABLE.is_ascii_compatible());
+        /// ```
+        pub const fn is_ascii_compatible(&self) -> bool {
+            match self {
+                TableType::Complete(_) | TableType::Incomplete(_) => true,
+            }
+        }
+    }
+
+    /// Compares the wrapped tables by pointer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::code_table::{CP437_TABLE, CP850_TABLE, DECODING_TABLE_CP437};
+    /// use oem_cp::code_table_type::TableType;
+    ///
+    /// assert_eq!(CP437_TABLE, TableType::complete(&DECODING_TABLE_CP437));
+    /// assert_ne!(CP437_TABLE, CP850_TABLE);
+    /// ```
+    impl PartialEq for TableType {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (TableType::Complete(a), TableType::Complete(b)) => core::ptr::eq(*a, *b),
+                (TableType::Incomplete(a), TableType::Incomplete(b)) => core::ptr::eq(*a, *b),
+                _ => false,
+            }
+        }
+    }
+
+    impl Eq for TableType {}
+
+    /// Hashes the pointer to the wrapped table (consistent with `PartialEq`)
+    impl Hash for TableType {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            match self {
+                TableType::Complete(table) => {
+                    state.write_u8(0);
+                    core::ptr::hash(*table, state);
+                }
+                TableType::Incomplete(table) => {
+                    state.write_u8(1);
+                    core::ptr::hash(*table, state);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,258 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::strategy::{AsciiFast, DecodeStrategy, EncodeStrategy};
+
+/// Error returned when a [`RuntimeTable`] can't be built from the given mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeTableError {
+    /// The byte is mapped to a value that isn't a Unicode scalar value (e.g. a surrogate or a value above U+10FFFF)
+    InvalidScalar {
+        /// Byte in 0x80-0xFF
+        byte: u8,
+        /// Value the byte is mapped to
+        value: u32,
+    },
+    /// The byte is mapped to a noncharacter (U+FDD0-U+FDEF or U+xFFFE/U+xFFFF), rejected in strict mode
+    Noncharacter {
+        /// Byte in 0x80-0xFF
+        byte: u8,
+        /// Noncharacter the byte is mapped to
+        c: char,
+    },
+}
+
+impl fmt::Display for RuntimeTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeTableError::InvalidScalar { byte, value } => write!(
+                f,
+                "0x{byte:02X} is mapped to 0x{value:X}, which isn't a Unicode scalar value"
+            ),
+            RuntimeTableError::Noncharacter { byte, c } => write!(
+                f,
+                "0x{byte:02X} is mapped to the noncharacter U+{:04X}",
+                *c as u32
+            ),
+        }
+    }
+}
+
+impl core::error::Error for RuntimeTableError {}
+
+/// Returns whether `c` is a noncharacter (U+FDD0-U+FDEF or the last two codepoints of each plane)
+const fn is_noncharacter(c: char) -> bool {
+    let c = c as u32;
+    matches!(c, 0xFDD0..=0xFDEF) || c & 0xFFFE == 0xFFFE
+}
+
+/// SBCS (single byte character set) table built at runtime from arbitrary data
+///
+/// Like the built-in tables, 0x00-0x7F are ASCII and only 0x80-0xFF are mapped by the table.
+/// Use this for code pages this crate doesn't ship.
+/// If several bytes are mapped to the same char, the lowest one is used for encoding.
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::RuntimeTable;
+///
+/// let mut mapping = [None; 128];
+/// mapping[0] = Some(0x20AC); // 0x80 => '€'
+/// let table = RuntimeTable::from_scalars(&mapping, true).unwrap();
+/// assert_eq!(table.decode_string_lossy(&[b'5', 0x80, 0x81]), "5€\u{FFFD}");
+/// assert_eq!(table.encode_string_lossy("5€£"), vec![b'5', 0x80, b'?']);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeTable {
+    decoding: [Option<char>; 128],
+    encoding: BTreeMap<char, u8>,
+}
+
+impl RuntimeTable {
+    /// Builds a table from the chars 0x80-0xFF are mapped to (`None` for undefined bytes)
+    ///
+    /// `char` never holds surrogates, so only noncharacters are checked, and only if `reject_noncharacters` is `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - chars 0x80-0xFF are mapped to
+    /// * `reject_noncharacters` - whether to fail on noncharacters (strict mode)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{RuntimeTable, RuntimeTableError};
+    ///
+    /// let mut mapping = [None; 128];
+    /// mapping[1] = Some('\u{FFFE}');
+    /// assert_eq!(
+    ///     RuntimeTable::from_chars(mapping, true),
+    ///     Err(RuntimeTableError::Noncharacter { byte: 0x81, c: '\u{FFFE}' })
+    /// );
+    /// assert!(RuntimeTable::from_chars(mapping, false).is_ok());
+    /// ```
+    pub fn from_chars(
+        mapping: [Option<char>; 128],
+        reject_noncharacters: bool,
+    ) -> Result<Self, RuntimeTableError> {
+        let mut encoding = BTreeMap::new();
+        for (byte, c) in (0x80..=0xFF).zip(mapping) {
+            let Some(c) = c else {
+                continue;
+            };
+            if reject_noncharacters && is_noncharacter(c) {
+                return Err(RuntimeTableError::Noncharacter { byte, c });
+            }
+            encoding.entry(c).or_insert(byte);
+        }
+        Ok(Self {
+            decoding: mapping,
+            encoding,
+        })
+    }
+
+    /// Builds a table from the code points 0x80-0xFF are mapped to (`None` for undefined bytes)
+    ///
+    /// Fails if any value isn't a Unicode scalar value (e.g. a surrogate), or is a noncharacter and `reject_noncharacters` is `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - code points 0x80-0xFF are mapped to
+    /// * `reject_noncharacters` - whether to fail on noncharacters (strict mode)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oem_cp::{RuntimeTable, RuntimeTableError};
+    ///
+    /// let mut mapping = [None; 128];
+    /// mapping[0] = Some(0xD800);
+    /// assert_eq!(
+    ///     RuntimeTable::from_scalars(&mapping, false),
+    ///     Err(RuntimeTableError::InvalidScalar { byte: 0x80, value: 0xD800 })
+    /// );
+    /// ```
+    pub fn from_scalars(
+        mapping: &[Option<u32>; 128],
+        reject_noncharacters: bool,
+    ) -> Result<Self, RuntimeTableError> {
+        let mut chars = [None; 128];
+        for ((byte, value), c) in (0x80..=0xFF).zip(mapping).zip(&mut chars) {
+            let Some(value) = *value else {
+                continue;
+            };
+            *c = Some(
+                char::from_u32(value).ok_or(RuntimeTableError::InvalidScalar { byte, value })?,
+            );
+        }
+        Self::from_chars(chars, reject_noncharacters)
+    }
+
+    /// Decode SBCS byte, returning `None` if it is undefined
+    pub fn decode_char_checked(&self, src: u8) -> Option<char> {
+        match AsciiFast::decode_direct(src) {
+            Ok(c) => Some(c),
+            Err(index) => self.decoding[index],
+        }
+    }
+
+    /// Decode SBCS bytes, returning `None` if any of them is undefined
+    pub fn decode_string_checked(&self, src: &[u8]) -> Option<String> {
+        src.iter()
+            .map(|byte| self.decode_char_checked(*byte))
+            .collect()
+    }
+
+    /// Decode SBCS bytes
+    ///
+    /// Undefined codepoints are replaced with U+FFFD.
+    pub fn decode_string_lossy(&self, src: &[u8]) -> String {
+        src.iter()
+            .map(|byte| self.decode_char_checked(*byte).unwrap_or('\u{FFFD}'))
+            .collect()
+    }
+
+    /// Encode Unicode char in SBCS, returning `None` if it's undefined
+    pub fn encode_char_checked(&self, src: char) -> Option<u8> {
+        AsciiFast::encode_direct(src).or_else(|| self.encoding.get(&src).copied())
+    }
+
+    /// Encode Unicode string in SBCS, returning `None` if any char is undefined
+    pub fn encode_string_checked(&self, src: &str) -> Option<Vec<u8>> {
+        src.chars().map(|c| self.encode_char_checked(c)).collect()
+    }
+
+    /// Encode Unicode string in SBCS
+    ///
+    /// Undefined codepoints are replaced with `0x3F` (`?`).
+    pub fn encode_string_lossy(&self, src: &str) -> Vec<u8> {
+        src.chars()
+            .map(|c| self.encode_char_checked(c).unwrap_or(b'?'))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_table::{CP874_TABLE, ENCODING_TABLE_CP874};
+
+    #[test]
+    fn runtime_table_test() {
+        let mapping = core::array::from_fn(|i| CP874_TABLE.decode_char_checked(0x80 + i as u8));
+        let table = RuntimeTable::from_chars(mapping, true).unwrap();
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(
+            table.decode_string_lossy(&bytes),
+            CP874_TABLE.decode_string_lossy(&bytes)
+        );
+        assert_eq!(table.decode_string_checked(&bytes), None);
+        let decoded = CP874_TABLE
+            .decode_string_lossy(&bytes)
+            .replace('\u{FFFD}', "");
+        assert_eq!(
+            table.encode_string_checked(&decoded),
+            crate::encode_string_checked(&decoded, &ENCODING_TABLE_CP874)
+        );
+        assert_eq!(table.encode_string_lossy("日"), b"?");
+        let scalars = mapping.map(|c| c.map(u32::from));
+        assert_eq!(RuntimeTable::from_scalars(&scalars, true), Ok(table));
+    }
+
+    #[test]
+    fn runtime_table_validation_test() {
+        for nonchar in ['\u{FDD0}', '\u{FDEF}', '\u{FFFE}', '\u{FFFF}', '\u{10FFFF}'] {
+            let mut mapping = [None; 128];
+            mapping[0x7F] = Some(nonchar);
+            assert_eq!(
+                RuntimeTable::from_chars(mapping, true),
+                Err(RuntimeTableError::Noncharacter {
+                    byte: 0xFF,
+                    c: nonchar
+                })
+            );
+            let table = RuntimeTable::from_chars(mapping, false).unwrap();
+            assert_eq!(table.decode_char_checked(0xFF), Some(nonchar));
+        }
+        for value in [0xD800, 0xDFFF, 0x110000] {
+            let mut mapping = [None; 128];
+            mapping[3] = Some(value);
+            assert_eq!(
+                RuntimeTable::from_scalars(&mapping, false),
+                Err(RuntimeTableError::InvalidScalar { byte: 0x83, value })
+            );
+        }
+        assert!(!is_noncharacter('\u{FDCF}'));
+        assert!(!is_noncharacter('\u{FFFD}'));
+    }
+
+    #[test]
+    fn runtime_table_duplicate_test() {
+        // The lowest byte wins
+        let table = RuntimeTable::from_chars([Some('€'); 128], true).unwrap();
+        assert_eq!(table.encode_char_checked('€'), Some(0x80));
+    }
+}
@@ -0,0 +1,147 @@
+//! Strategies that decide which bytes and chars are mapped without looking up tables
+//!
+//! All the supported code pages use [`AsciiFast`].
+//! Every function that special-cases ASCII goes through these, so code pages remapping 0x00-0x7F only need another strategy.
+
+/// How bytes are decoded
+pub(crate) trait DecodeStrategy {
+    /// Returns the char `byte` is decoded to without tables (`Ok`), or the index of `byte` in the decoding table (`Err`)
+    fn decode_direct(byte: u8) -> Result<char, usize>;
+}
+
+/// How chars are encoded
+pub(crate) trait EncodeStrategy {
+    /// Returns the byte `c` is encoded to without tables, or `None` if the encoding table has to be looked up
+    fn encode_direct(c: char) -> Option<u8>;
+}
+
+/// Strategy for ASCII compatible code pages
+///
+/// 0x00-0x7F are mapped to U+0000-U+007F, and decoding tables cover 0x80-0xFF.
+pub(crate) struct AsciiFast;
+
+impl DecodeStrategy for AsciiFast {
+    #[inline]
+    fn decode_direct(byte: u8) -> Result<char, usize> {
+        if byte < 128 {
+            Ok(byte as char)
+        } else {
+            Err((byte & 127) as usize)
+        }
+    }
+}
+
+impl EncodeStrategy for AsciiFast {
+    #[inline]
+    fn encode_direct(c: char) -> Option<u8> {
+        if (c as u32) < 128 {
+            Some(c as u8)
+        } else {
+            None
+        }
+    }
+}
+
+/// Strategy for code pages remapping all the bytes (e.g. VISCII and GSM 03.38)
+///
+/// Decoding tables cover 0x00-0xFF.
+#[allow(dead_code)] // No supported code pages need it yet
+pub(crate) struct FullRange;
+
+impl DecodeStrategy for FullRange {
+    #[inline]
+    fn decode_direct(byte: u8) -> Result<char, usize> {
+        Err(byte as usize)
+    }
+}
+
+impl EncodeStrategy for FullRange {
+    #[inline]
+    fn encode_direct(_c: char) -> Option<u8> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strategy_test() {
+        for byte in 0..=255u8 {
+            assert_eq!(FullRange::decode_direct(byte), Err(byte as usize));
+            match AsciiFast::decode_direct(byte) {
+                Ok(c) => assert_eq!(c as u32, byte as u32),
+                Err(index) => assert_eq!(index + 128, byte as usize),
+            }
+        }
+        assert_eq!(AsciiFast::encode_direct('A'), Some(b'A'));
+        assert_eq!(AsciiFast::encode_direct('\u{80}'), None);
+        assert_eq!(FullRange::encode_direct('A'), None);
+    }
+
+    /// Compares with the implementations before the strategies were introduced
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn ascii_fast_identical_test() {
+        use crate::code_table::{DECODING_TABLE_CP_MAP, ENCODING_TABLE_CP_MAP};
+        use crate::code_table_type::TableType;
+        use crate::*;
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        let chars = (0..0x3000)
+            .filter_map(char::from_u32)
+            .chain(['\u{FFFD}', '\u{10000}'])
+            .collect::<String>();
+        for (code_page, table) in DECODING_TABLE_CP_MAP.entries() {
+            let encoding_table = ENCODING_TABLE_CP_MAP[code_page];
+            let old_decode = |byte: u8| match table {
+                TableType::Complete(table) if byte >= 128 => Some(table[(byte & 127) as usize]),
+                TableType::Incomplete(table) if byte >= 128 => table[(byte & 127) as usize],
+                _ => Some(byte as char),
+            };
+            let old_encode = |c: char| {
+                if (c as u32) < 128 {
+                    Some(c as u8)
+                } else {
+                    encoding_table.get(&c).copied()
+                }
+            };
+            for &byte in &bytes {
+                assert_eq!(table.decode_char_checked(byte), old_decode(byte));
+                assert_eq!(
+                    table.decode_char_lossy(byte),
+                    old_decode(byte).unwrap_or('\u{FFFD}')
+                );
+            }
+            assert_eq!(
+                table.decode_string_lossy(&bytes),
+                bytes
+                    .iter()
+                    .map(|byte| old_decode(*byte).unwrap_or('\u{FFFD}'))
+                    .collect::<String>()
+            );
+            assert_eq!(
+                table.decode_string_checked(&bytes),
+                bytes.iter().map(|byte| old_decode(*byte)).collect()
+            );
+            for c in chars.chars() {
+                assert_eq!(encode_char_checked(c, encoding_table), old_encode(c));
+                assert_eq!(
+                    encode_char_lossy(c, encoding_table),
+                    old_encode(c).unwrap_or(b'?')
+                );
+            }
+            assert_eq!(
+                encode_string_lossy(&chars, encoding_table),
+                chars
+                    .chars()
+                    .map(|c| old_encode(c).unwrap_or(b'?'))
+                    .collect::<Vec<u8>>()
+            );
+            assert_eq!(
+                encode_string_checked(&chars, encoding_table),
+                chars.chars().map(old_encode).collect()
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use super::{encode_string_checked, IncompleteCp};
+
+/// Assert that `src` is encoded in the code page `T` and decoded back to the same string
+///
+/// Panics if `src` has characters undefined in `T` or the round trip changes it.
+/// Use [`assert_roundtrip!`](crate::assert_roundtrip) for brevity.
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{assert_cp_roundtrip, Cp437, Cp874};
+///
+/// assert_cp_roundtrip::<Cp437>("√¼=½");
+/// // means shrimp in Thai
+/// assert_cp_roundtrip::<Cp874>("กุ้ง");
+/// ```
+#[track_caller]
+pub fn assert_cp_roundtrip<T: IncompleteCp>(src: &str) {
+    let encoded = encode_string_checked(src, T::encoding_table())
+        .unwrap_or_else(|| panic!("{src:?} can't be encoded in cp{}", T::ID));
+    let decoded = T::decoding_table()
+        .decode_string_checked(&encoded)
+        .unwrap_or_else(|| {
+            panic!(
+                "{encoded:X?} (encoded from {src:?}) can't be decoded in cp{}",
+                T::ID
+            )
+        });
+    assert_eq!(
+        decoded,
+        src,
+        "round trip in cp{} changed {src:?} (encoded: {encoded:X?})",
+        T::ID
+    );
+}
+
+/// Assert that a string is encoded in a code page and decoded back to the same string
+///
+/// `assert_roundtrip!(Cp437, s)` is a shorthand for `assert_cp_roundtrip::<Cp437>(s)`.
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{assert_roundtrip, Cp437};
+///
+/// assert_roundtrip!(Cp437, "√¼=½");
+/// ```
+#[macro_export]
+macro_rules! assert_roundtrip {
+    ($cp:ty, $src:expr $(,)?) => {
+        $crate::assert_cp_roundtrip::<$cp>($src)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cp437, Cp857};
+
+    #[test]
+    fn assert_roundtrip_test() {
+        assert_roundtrip!(Cp437, "π≈22/7");
+        assert_roundtrip!(Cp857, "İran");
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be encoded in cp437")]
+    fn assert_roundtrip_unencodable_test() {
+        assert_roundtrip!(Cp437, "日本語");
+    }
+}
@@ -0,0 +1,240 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use super::{encode_char_checked, encode_char_lossy, OEMCPHashMap};
+
+/// Provides replacement strings for characters that can't be encoded
+pub trait Transliterator {
+    /// Returns the replacement of `c`, or `None` if there is no replacement
+    fn transliterate(&self, c: char) -> Option<&str>;
+}
+
+/// Returns a single similar char for best-fit encoding
+///
+/// This is a small heuristic, not Windows' best-fit tables: fullwidth ASCII falls back to ASCII, and otherwise single-char replacements of [`LatinTransliterator`] are used (e.g. `é` → `e`, `×` → `x`).
+/// Replacements longer than one char (e.g. `€` → `EUR`) aren't used.
+pub(crate) fn best_fit_char(c: char) -> Option<char> {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0),
+        '\u{3000}' => Some(' '),
+        _ => {
+            let mut chars = LatinTransliterator.transliterate(c)?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Transliterator into basic Latin (ASCII)
+///
+/// It handles Latin letters with diacritics (e.g. `é` => `e`), ligatures (e.g. `ß` => `ss`), typographic punctuation (e.g. `–` => `-`), some symbols (e.g. `€` => `EUR`), and Russian Cyrillic letters (e.g. `Ж` => `Zh`).
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{LatinTransliterator, Transliterator};
+///
+/// assert_eq!(LatinTransliterator.transliterate('ł'), Some("l"));
+/// assert_eq!(LatinTransliterator.transliterate('Щ'), Some("Shch"));
+/// assert_eq!(LatinTransliterator.transliterate('日'), None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatinTransliterator;
+
+impl Transliterator for LatinTransliterator {
+    fn transliterate(&self, c: char) -> Option<&str> {
+        // cspell: disable
+        Some(match c {
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+            'Æ' => "AE",
+            'æ' => "ae",
+            'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+            'Ď' | 'Đ' | 'Ð' => "D",
+            'ď' | 'đ' | 'ð' => "d",
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+            'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+            'Ĥ' | 'Ħ' => "H",
+            'ĥ' | 'ħ' => "h",
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+            'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+            'Ĵ' => "J",
+            'ĵ' => "j",
+            'Ķ' => "K",
+            'ķ' => "k",
+            'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+            'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+            'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+            'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+            'Œ' => "OE",
+            'œ' => "oe",
+            'Ŕ' | 'Ŗ' | 'Ř' => "R",
+            'ŕ' | 'ŗ' | 'ř' => "r",
+            'Ś' | 'Ŝ' | 'Ş' | 'Š' | 'Ș' => "S",
+            'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+            'ß' => "ss",
+            'Ţ' | 'Ť' | 'Ŧ' | 'Ț' => "T",
+            'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+            'Þ' => "Th",
+            'þ' => "th",
+            'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+            'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+            'Ŵ' => "W",
+            'ŵ' => "w",
+            'Ý' | 'Ÿ' | 'Ŷ' => "Y",
+            'ý' | 'ÿ' | 'ŷ' => "y",
+            'Ź' | 'Ż' | 'Ž' => "Z",
+            'ź' | 'ż' | 'ž' => "z",
+            'А' => "A",
+            'Б' => "B",
+            'В' => "V",
+            'Г' => "G",
+            'Д' => "D",
+            'Е' => "E",
+            'Ё' => "Yo",
+            'Ж' => "Zh",
+            'З' => "Z",
+            'И' => "I",
+            'Й' => "Y",
+            'К' => "K",
+            'Л' => "L",
+            'М' => "M",
+            'Н' => "N",
+            'О' => "O",
+            'П' => "P",
+            'Р' => "R",
+            'С' => "S",
+            'Т' => "T",
+            'У' => "U",
+            'Ф' => "F",
+            'Х' => "Kh",
+            'Ц' => "Ts",
+            'Ч' => "Ch",
+            'Ш' => "Sh",
+            'Щ' => "Shch",
+            'Ъ' | 'Ь' | 'ъ' | 'ь' => "",
+            'Ы' => "Y",
+            'Э' => "E",
+            'Ю' => "Yu",
+            'Я' => "Ya",
+            'а' => "a",
+            'б' => "b",
+            'в' => "v",
+            'г' => "g",
+            'д' => "d",
+            'е' => "e",
+            'ё' => "yo",
+            'ж' => "zh",
+            'з' => "z",
+            'и' => "i",
+            'й' => "y",
+            'к' => "k",
+            'л' => "l",
+            'м' => "m",
+            'н' => "n",
+            'о' => "o",
+            'п' => "p",
+            'р' => "r",
+            'с' => "s",
+            'т' => "t",
+            'у' => "u",
+            'ф' => "f",
+            'х' => "kh",
+            'ц' => "ts",
+            'ч' => "ch",
+            'ш' => "sh",
+            'щ' => "shch",
+            'ы' => "y",
+            'э' => "e",
+            'ю' => "yu",
+            'я' => "ya",
+            '\u{A0}' | '\u{2002}'..='\u{200A}' => " ",
+            '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => "-",
+            '‘' | '’' | '‚' | '‛' | '′' => "'",
+            '“' | '”' | '„' | '‟' | '″' | '«' | '»' => "\"",
+            '‹' => "<",
+            '›' => ">",
+            '…' => "...",
+            '•' | '·' => "*",
+            '€' => "EUR",
+            '£' => "GBP",
+            '¥' => "JPY",
+            '©' => "(C)",
+            '®' => "(R)",
+            '™' => "TM",
+            '×' => "x",
+            '÷' => "/",
+            _ => return None,
+        })
+        // cspell: enable
+    }
+}
+
+/// Encode Unicode string in SBCS (single byte character set) with transliteration
+///
+/// Characters undefined in the code page are replaced with the string `transliterator` returns, which is encoded in turn.
+/// Characters that still can't be encoded (or have no replacement) are replaced with `0x3F` (`?`).
+///
+/// # Arguments
+///
+/// * `src` - Unicode string
+/// * `encoding_table` - table for encoding in SBCS
+/// * `transliterator` - provider of replacements for characters undefined in the code page
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::{encode_string_translit, LatinTransliterator};
+/// use oem_cp::code_table::{ENCODING_TABLE_CP437, ENCODING_TABLE_CP866};
+///
+/// // "Привет" is transliterated in CP437...
+/// assert_eq!(encode_string_translit("Привет", &ENCODING_TABLE_CP437, &LatinTransliterator), b"Privet");
+/// // ...but not in CP866
+/// assert_eq!(encode_string_translit("Привет", &ENCODING_TABLE_CP866, &LatinTransliterator), vec![0x8F, 0xE0, 0xA8, 0xA2, 0xA5, 0xE2]);
+/// // "é" is defined in CP437, whereas "ł" and "日" aren't
+/// assert_eq!(encode_string_translit("éł日", &ENCODING_TABLE_CP437, &LatinTransliterator), vec![0x82, b'l', b'?']);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encode_string_translit(
+    src: &str,
+    encoding_table: &OEMCPHashMap<char, u8>,
+    transliterator: &dyn Transliterator,
+) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(src.len());
+    for c in src.chars() {
+        if let Some(byte) = encode_char_checked(c, encoding_table) {
+            ret.push(byte);
+        } else if let Some(replacement) = transliterator.transliterate(c) {
+            ret.extend(
+                replacement
+                    .chars()
+                    .map(|c| encode_char_lossy(c, encoding_table)),
+            );
+        } else {
+            ret.push(b'?');
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latin_transliterator_ascii_test() {
+        for c in (0x80..0x3000).filter_map(char::from_u32) {
+            if let Some(replacement) = LatinTransliterator.transliterate(c) {
+                assert!(replacement.is_ascii(), "{c:?} => {replacement:?}");
+            }
+        }
+    }
+}
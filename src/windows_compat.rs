@@ -0,0 +1,80 @@
+//! Comparison with the conversion of Windows API (`MultiByteToWideChar`)
+
+use std::vec::Vec;
+
+use super::code_table::DECODING_TABLE_CP_MAP;
+
+/// Compare the decoding table of a code page with Windows API
+///
+/// Decodes each byte in 0x80-0xFF with this crate and `MultiByteToWideChar` of the running system, and returns `(byte, library, windows)` for the bytes they differ in.
+/// `None` means the byte is undefined (or the code page isn't supported by this crate or the system).
+/// Private use characters (U+E000-U+F8FF) that Windows maps some undefined bytes to are also treated as `None`.
+///
+/// An empty result means the table matches the system.
+/// The `dos` dialect is expected to differ (see the README).
+///
+/// Available only on Windows with the `windows-compat` feature.
+///
+/// # Arguments
+///
+/// * `cp` - code page ID
+///
+/// # Examples
+///
+/// ```
+/// use oem_cp::diff_against_windows;
+///
+/// for (byte, library, windows) in diff_against_windows(437) {
+///     println!("0x{byte:02X}: {library:?} (library) / {windows:?} (Windows)");
+/// }
+/// ```
+pub fn diff_against_windows(cp: u16) -> Vec<(u8, Option<char>, Option<char>)> {
+    let table = DECODING_TABLE_CP_MAP.get(&cp);
+    (0x80..=0xFF)
+        .filter_map(|byte| {
+            let library = table.and_then(|table| table.decode_char_checked(byte));
+            let windows = windows_decode_char(byte, cp);
+            (library != windows).then_some((byte, library, windows))
+        })
+        .collect()
+}
+
+/// Decodes single byte with `MultiByteToWideChar`, returning `None` if it fails
+fn windows_decode_char(byte: u8, cp: u16) -> Option<char> {
+    use winapi::um::stringapiset::MultiByteToWideChar;
+    use winapi::um::winnls::MB_ERR_INVALID_CHARS;
+
+    let src = [byte];
+    let mut buf = [0u16; 2];
+    // SAFETY: `src` and `buf` are valid for the lengths passed
+    let len = unsafe {
+        MultiByteToWideChar(
+            cp as u32,
+            MB_ERR_INVALID_CHARS,
+            src.as_ptr().cast(),
+            src.len() as i32,
+            buf.as_mut_ptr(),
+            buf.len() as i32,
+        )
+    };
+    let mut chars = char::decode_utf16(buf[..usize::try_from(len).ok()?].iter().copied());
+    match (chars.next(), chars.next()) {
+        (Some(Ok(c)), None) if !('\u{E000}'..='\u{F8FF}').contains(&c) => Some(c),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The dialect other than `win` is expected to differ from Windows API
+    #[cfg(oem_cp_dialect = "win")]
+    #[test]
+    fn diff_against_windows_test() {
+        for code_page in DECODING_TABLE_CP_MAP.keys() {
+            let diff = diff_against_windows(*code_page);
+            assert!(diff.is_empty(), "cp{code_page}: {diff:?}");
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Checks that the names in the generated `code_table` module stay stable
+//!
+//! Downstream crates import these items directly, so renaming them in `build.rs` is a breaking change.
+
+use oem_cp::code_table::{DECODING_TABLE_CP_MAP, ENCODING_TABLE_CP_MAP};
+use oem_cp::code_table_type::TableType;
+use oem_cp::OEMCPHashMap;
+
+/// Imports `DECODING_TABLE_CP$id`, `ENCODING_TABLE_CP$id`, and `DEFINED_COUNT_CP$id` of each code page and checks them against the maps
+macro_rules! check_tables {
+    ($($id:literal => $decoding:ident, $encoding:ident, $count:ident;)*) => {
+        $(
+            {
+                use oem_cp::code_table::{$count, $decoding, $encoding};
+
+                assert_eq!($decoding.len(), 128);
+                let encoding: &OEMCPHashMap<char, u8> = &$encoding;
+                assert!(core::ptr::eq(encoding, ENCODING_TABLE_CP_MAP[&$id]));
+                let decoding: &TableType = &DECODING_TABLE_CP_MAP[&$id];
+                assert_eq!(usize::from($count), 128 - decoding.undefined_bytes().count());
+                for byte in 128..=255u8 {
+                    assert_eq!(
+                        decoding.decode_char_checked(byte).map(|c| encoding[&c]),
+                        decoding.decode_char_checked(byte).map(|_| byte),
+                        "0x{byte:X} in CP{}", $id
+                    );
+                }
+            }
+        )*
+        assert_eq!(DECODING_TABLE_CP_MAP.len(), [$($id),*].len());
+        assert_eq!(ENCODING_TABLE_CP_MAP.len(), [$($id),*].len());
+    };
+}
+
+#[test]
+fn code_table_names_test() {
+    check_tables! {
+        437 => DECODING_TABLE_CP437, ENCODING_TABLE_CP437, DEFINED_COUNT_CP437;
+        720 => DECODING_TABLE_CP720, ENCODING_TABLE_CP720, DEFINED_COUNT_CP720;
+        737 => DECODING_TABLE_CP737, ENCODING_TABLE_CP737, DEFINED_COUNT_CP737;
+        775 => DECODING_TABLE_CP775, ENCODING_TABLE_CP775, DEFINED_COUNT_CP775;
+        850 => DECODING_TABLE_CP850, ENCODING_TABLE_CP850, DEFINED_COUNT_CP850;
+        852 => DECODING_TABLE_CP852, ENCODING_TABLE_CP852, DEFINED_COUNT_CP852;
+        855 => DECODING_TABLE_CP855, ENCODING_TABLE_CP855, DEFINED_COUNT_CP855;
+        857 => DECODING_TABLE_CP857, ENCODING_TABLE_CP857, DEFINED_COUNT_CP857;
+        858 => DECODING_TABLE_CP858, ENCODING_TABLE_CP858, DEFINED_COUNT_CP858;
+        860 => DECODING_TABLE_CP860, ENCODING_TABLE_CP860, DEFINED_COUNT_CP860;
+        861 => DECODING_TABLE_CP861, ENCODING_TABLE_CP861, DEFINED_COUNT_CP861;
+        862 => DECODING_TABLE_CP862, ENCODING_TABLE_CP862, DEFINED_COUNT_CP862;
+        863 => DECODING_TABLE_CP863, ENCODING_TABLE_CP863, DEFINED_COUNT_CP863;
+        864 => DECODING_TABLE_CP864, ENCODING_TABLE_CP864, DEFINED_COUNT_CP864;
+        865 => DECODING_TABLE_CP865, ENCODING_TABLE_CP865, DEFINED_COUNT_CP865;
+        866 => DECODING_TABLE_CP866, ENCODING_TABLE_CP866, DEFINED_COUNT_CP866;
+        869 => DECODING_TABLE_CP869, ENCODING_TABLE_CP869, DEFINED_COUNT_CP869;
+        874 => DECODING_TABLE_CP874, ENCODING_TABLE_CP874, DEFINED_COUNT_CP874;
+    }
+}
+
+#[test]
+fn decoding_array_const_test() {
+    use oem_cp::code_table::{decoding_array_complete_cp, decoding_array_incomplete_cp};
+
+    const CP437: &[char; 128] = match decoding_array_complete_cp(437) {
+        Some(array) => array,
+        None => panic!(),
+    };
+    const CP874_0XDB: Option<char> = match decoding_array_incomplete_cp(874) {
+        Some(array) => array[0xDB - 0x80],
+        None => panic!(),
+    };
+    const _: () = assert!(
+        decoding_array_complete_cp(932).is_none() && decoding_array_incomplete_cp(932).is_none()
+    );
+    assert_eq!(CP437[0xFB - 0x80], '√');
+    assert_eq!(CP874_0XDB, None);
+
+    for (id, table) in DECODING_TABLE_CP_MAP.entries() {
+        match *table {
+            TableType::Complete(array) => {
+                assert!(core::ptr::eq(
+                    decoding_array_complete_cp(*id).unwrap(),
+                    array
+                ));
+                assert!(decoding_array_incomplete_cp(*id).is_none());
+            }
+            TableType::Incomplete(array) => {
+                assert!(core::ptr::eq(
+                    decoding_array_incomplete_cp(*id).unwrap(),
+                    array
+                ));
+                assert!(decoding_array_complete_cp(*id).is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Checks that decoding and encoding are mutual inverses for every generated table
+//!
+//! The tables are enumerated from the generated maps, so code pages added to `build.rs` are covered automatically.
+
+use oem_cp::code_table::{DECODING_TABLE_CP_MAP, ENCODING_TABLE_CP_MAP};
+use oem_cp::code_table_type::TableType;
+use oem_cp::encode_char_checked;
+
+#[test]
+fn decode_encode_inverse_test() {
+    assert_eq!(DECODING_TABLE_CP_MAP.len(), ENCODING_TABLE_CP_MAP.len());
+    for (code_page, table) in DECODING_TABLE_CP_MAP.entries() {
+        let encoding_table = ENCODING_TABLE_CP_MAP[code_page];
+        for byte in 0..=255u8 {
+            let decoded = table.decode_char_checked(byte);
+            if let TableType::Complete(_) = table {
+                assert!(decoded.is_some(), "0x{byte:02X} in CP{code_page}");
+            }
+            let Some(c) = decoded else {
+                continue;
+            };
+            assert_eq!(
+                encode_char_checked(c, encoding_table),
+                Some(byte),
+                "0x{byte:02X} in CP{code_page}"
+            );
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn decode_encode_string_inverse_test() {
+    use oem_cp::encode_string_checked;
+
+    for (code_page, table) in DECODING_TABLE_CP_MAP.entries() {
+        let defined = (0..=255u8)
+            .filter(|byte| table.decode_char_checked(*byte).is_some())
+            .collect::<Vec<_>>();
+        let decoded = table.decode_string_checked(&defined).unwrap();
+        assert_eq!(
+            encode_string_checked(&decoded, ENCODING_TABLE_CP_MAP[code_page]),
+            Some(defined),
+            "CP{code_page}"
+        );
+    }
+}
+
+#[test]
+fn encode_decode_inverse_test() {
+    for (code_page, encoding_table) in ENCODING_TABLE_CP_MAP.entries() {
+        let table = &DECODING_TABLE_CP_MAP[code_page];
+        for (c, byte) in encoding_table.entries() {
+            assert_eq!(
+                table.decode_char_checked(*byte),
+                Some(*c),
+                "{c:?} in CP{code_page}"
+            );
+        }
+    }
+}